@@ -1,7 +1,13 @@
-use std::{ops::{Index, IndexMut}, fmt::{Debug, self}, ops::Add};
+use std::{ops::{Index, IndexMut}, fmt::{Debug, Display, self}, ops::Add, str::FromStr};
+
+use serde::{
+    de::{self, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 
 /// Enumeration of available abilities.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Ability {
     Atmosphere,
     Diction,
@@ -28,6 +34,20 @@ impl Ability {
         ]
     }
 
+    /// Returns the name of the ability.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Ability::Atmosphere => "Atmosphere",
+            Ability::Diction => "Diction",
+            Ability::Precision => "Precision",
+            Ability::Calmness => "Calmness",
+            Ability::Focus => "Focus",
+            Ability::Style => "Style",
+            Ability::Rhythm => "Rhythm",
+            Ability::Timing => "Timing",
+        }
+    }
+
     /// Returns the cost of the ability.
     pub const fn cost(&self) -> u32 {
         match self {
@@ -43,8 +63,26 @@ impl Ability {
     }
 }
 
+impl FromStr for Ability {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ability::values()
+            .iter()
+            .find(|ability| ability.name() == s)
+            .copied()
+            .ok_or(())
+    }
+}
+
+impl Display for Ability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// Efficient ability counter.
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, Eq, Hash, PartialEq)]
 pub struct Abilities {
     abilities: [u8; 8],
 }
@@ -54,6 +92,14 @@ impl Abilities {
         Abilities { abilities: [0; 8] }
     }
 
+    /// Returns the total cost of the abilities.
+    pub fn cost(&self) -> u32 {
+        Ability::values()
+            .iter()
+            .map(|ability| self[*ability] as u32 * ability.cost())
+            .sum()
+    }
+
     const fn index_of(ability: Ability) -> usize {
         match ability {
             Ability::Atmosphere => 0,
@@ -88,6 +134,68 @@ impl Debug for Abilities {
     }
 }
 
+/// Formats the abilities by name, for example `2 Atmosphere, 10 Diction`.
+impl Display for Abilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut empty = true;
+        for ability in Ability::values() {
+            if self[ability] == 0 {
+                continue;
+            }
+            if !empty {
+                f.write_str(", ")?;
+            }
+            write!(f, "{} {}", self[ability], ability)?;
+            empty = false;
+        }
+        if empty {
+            f.write_str("None")?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializes the abilities as a map from ability name to count, omitting unused abilities.
+impl Serialize for Abilities {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for ability in Ability::values() {
+            if self[ability] > 0 {
+                map.serialize_entry(ability.name(), &self[ability])?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// Deserializes the abilities from a map from ability name to count.
+impl<'de> Deserialize<'de> for Abilities {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AbilitiesVisitor;
+
+        impl<'de> Visitor<'de> for AbilitiesVisitor {
+            type Value = Abilities;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map from ability name to count")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Abilities, A::Error> {
+                let mut output = Abilities::new();
+                while let Some((name, count)) = map.next_entry::<String, u8>()? {
+                    let ability = name
+                        .parse::<Ability>()
+                        .map_err(|_| de::Error::custom(format!("unknown ability {}", name)))?;
+                    output[ability] = count;
+                }
+                Ok(output)
+            }
+        }
+
+        deserializer.deserialize_map(AbilitiesVisitor)
+    }
+}
+
 impl Add<&Abilities> for Abilities {
     type Output = Abilities;
 
@@ -112,4 +220,24 @@ mod tests {
         ability_set[Ability::Diction] = 4;
         assert_eq!(ability_set[Ability::Diction], 4);
     }
+
+    #[test]
+    fn test_display() {
+        let mut abilities = Abilities::new();
+        assert_eq!(abilities.to_string(), "None");
+        abilities[Ability::Atmosphere] = 2;
+        abilities[Ability::Timing] = 1;
+        assert_eq!(abilities.to_string(), "2 Atmosphere, 1 Timing");
+        assert_eq!("Timing".parse::<Ability>(), Ok(Ability::Timing));
+    }
+
+    #[test]
+    fn test_serde() {
+        let mut abilities = Abilities::new();
+        abilities[Ability::Diction] = 3;
+        let json = serde_json::to_string(&abilities).unwrap();
+        assert_eq!(json, r#"{"Diction":3}"#);
+        assert_eq!(serde_json::from_str::<Abilities>(&json).unwrap(), abilities);
+        assert!(serde_json::from_str::<Abilities>(r#"{"Luck":1}"#).is_err());
+    }
 }
@@ -0,0 +1,148 @@
+use clap::Clap;
+
+use btv::{
+    challenges::{Challenge, TARGET_SET},
+    constraints::Constraint,
+    rewards::Rewards,
+    solver::best_within,
+    state::GameState,
+    Solver,
+};
+
+use super::{parallel_map, BoardArgs};
+
+/// Arguments of the `analyze` command.
+#[derive(Clap)]
+pub struct AnalyzeArgs {
+    #[clap(flatten)]
+    board: BoardArgs,
+
+    /// The total cost of the abilities to analyze the plans at.
+    #[clap(long)]
+    budget: u32,
+}
+
+/// A change to the board.
+struct Perturbation {
+    name: String,
+    challenges: Vec<Challenge>,
+}
+
+pub fn run(args: AnalyzeArgs) {
+    let board = args.board.load();
+
+    eprintln!("Searching for optimal challenge orders");
+    let rows = sensitivity(
+        &board.solver,
+        &board.challenges,
+        &board.state,
+        &board.constraints,
+        args.budget,
+    );
+    let baseline = rows[0].1;
+
+    println!();
+    println!("{:<50} {:>8} {:>9}", "Perturbation", "Success", "Change");
+    println!("{:<50} {:>8.4}", format!("Baseline (budget {})", args.budget), baseline);
+    for (name, proba) in &rows[1..] {
+        println!("{:<50} {:>8.4} {:>+9.4}", name, proba, proba - baseline);
+    }
+}
+
+/// Returns the best success chance at the budget for the board and for each change to it.
+///
+/// The first row holds the unchanged board, followed by two extra points and the perturbations.
+pub fn sensitivity(
+    solver: &Solver,
+    challenges: &[Challenge],
+    state: &GameState,
+    constraints: &[Constraint],
+    budget: u32,
+) -> Vec<(String, f32)> {
+    // Collect the changes to the board.
+    let mut perturbations = Vec::new();
+    for (idx, challenge) in challenges.iter().enumerate() {
+        if state.is_completed(idx) {
+            continue;
+        }
+        if challenge.reward != Rewards::NONE {
+            let mut challenges = challenges.to_vec();
+            challenges[idx].reward = Rewards::NONE;
+            perturbations.push(Perturbation {
+                name: format!("{} without reward", challenge.name),
+                challenges,
+            });
+        }
+        let steps = [challenge.target_idx.checked_sub(1), Some(challenge.target_idx + 1)];
+        for target_idx in steps.iter().flatten().filter(|t| **t < TARGET_SET.len()) {
+            let mut challenges = challenges.to_vec();
+            challenges[idx].target_idx = *target_idx;
+            perturbations.push(Perturbation {
+                name: format!(
+                    "{} target {} -> {}",
+                    challenge.name, TARGET_SET[challenge.target_idx], TARGET_SET[*target_idx]
+                ),
+                challenges,
+            });
+        }
+    }
+
+    let frontier = solver.solve_from(challenges, state, constraints);
+    let success = |budget| best_within(&frontier, budget).map_or(0.0, |v| v.log_proba.exp());
+    let baseline = success(budget);
+    let results = parallel_map(&perturbations, |perturbation| {
+        let frontier = solver.solve_from(&perturbation.challenges, state, constraints);
+        best_within(&frontier, budget).map_or(0.0, |v| v.log_proba.exp())
+    });
+
+    let mut rows = vec![
+        ("Baseline".to_string(), baseline),
+        ("Budget +2".to_string(), success(budget.saturating_add(2))),
+    ];
+    rows.extend(perturbations.into_iter().map(|p| p.name).zip(results));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::*, *};
+
+    #[test]
+    fn test_sensitivity() {
+        let (challenges, state) = board();
+        let rows = sensitivity(solver(), &challenges, &state, &[], 60);
+        let names: Vec<&str> = rows.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "Baseline",
+                "Budget +2",
+                "Monologue without reward",
+                "Monologue target 20 -> 11",
+                "Monologue target 20 -> 25",
+                "Quickteams target 10 -> 11",
+            ]
+        );
+        let baseline = rows[0].1;
+        assert!(baseline > 0.0);
+        assert!(rows[1].1 >= baseline);
+        assert!(rows[3].1 >= baseline);
+        assert!(rows[4].1 <= baseline);
+        assert!(rows[5].1 <= baseline);
+    }
+
+    #[test]
+    fn test_sensitivity_completed() {
+        let (challenges, mut state) = board();
+        state.completed.push(0);
+        let rows = sensitivity(solver(), &challenges, &state, &[], 60);
+        assert!(rows[2..].iter().all(|(name, _)| name.starts_with("Quickteams")));
+    }
+
+    #[test]
+    fn test_sensitivity_saturated_budget() {
+        let (challenges, state) = board();
+        let rows = sensitivity(solver(), &challenges, &state, &[], u32::MAX);
+        assert_eq!(rows[1].1, rows[0].1);
+    }
+}
@@ -0,0 +1,99 @@
+use clap::Clap;
+
+use btv::{parser, solver};
+
+use super::{board_paths, parallel_map, read_file, SolverArgs};
+
+/// Arguments of the `batch` command.
+#[derive(Clap)]
+pub struct BatchArgs {
+    /// The paths of the input files, or of directories holding them. `-` reads a board from
+    /// stdin.
+    #[clap(required = true)]
+    inputs: Vec<String>,
+
+    /// The points to buy abilities with. Defaults to an unlimited budget.
+    #[clap(long)]
+    budget: Option<u32>,
+
+    /// The success chance to report the cheapest plan reaching. Defaults to 0.9.
+    #[clap(long)]
+    target: Option<f32>,
+
+    #[clap(flatten)]
+    solver: SolverArgs,
+}
+
+/// The default success chance to report the cost of.
+const TARGET: f32 = 0.9;
+
+/// A row of the summary table.
+struct Row {
+    board: String,
+
+    /// Whether the board could be read and parsed.
+    valid: bool,
+
+    /// The best success chance within the budget.
+    best: Option<f32>,
+
+    /// The cost of the cheapest plan reaching the target success chance.
+    cost: Option<u32>,
+}
+
+pub fn run(args: BatchArgs) {
+    // Read every board upfront, since stdin can only be read once.
+    let boards: Vec<(String, Option<String>)> = board_paths(&args.inputs)
+        .into_iter()
+        .map(|path| match read_file(&path) {
+            Ok(text) => (path, Some(text)),
+            Err(err) => {
+                eprintln!("Cannot read {}: {}", path, err);
+                (path, None)
+            }
+        })
+        .collect();
+
+    // The challenge setups are loaded once and shared by every board.
+    let solver = args.solver.build();
+    let budget = args.budget.unwrap_or(u32::MAX);
+    let target = args.target.unwrap_or(TARGET);
+    eprintln!("Solving {} boards", boards.len());
+    let rows = parallel_map(&boards, |(path, text)| {
+        let challenges = match text.as_ref().and_then(|text| parser::parse(text).ok()) {
+            Some(challenges) if !challenges.is_empty() => challenges,
+            _ => {
+                return Row {
+                    board: path.clone(),
+                    valid: false,
+                    best: None,
+                    cost: None,
+                }
+            }
+        };
+        let frontier = solver.solve(&challenges);
+        Row {
+            board: path.clone(),
+            valid: true,
+            best: solver::best_within(&frontier, budget).map(|v| v.log_proba.exp()),
+            cost: solver::cheapest_reaching(&frontier, target).map(|v| v.cost),
+        }
+    });
+
+    let width = rows.iter().map(|row| row.board.len()).max().unwrap_or(0).max(5);
+    let budget_header = match args.budget {
+        Some(budget) => format!("Best at {}", budget),
+        None => "Best".to_string(),
+    };
+    let cost_header = format!("Cost for {}", target);
+    println!("{:<width$}  {:>12}  {:>12}", "Board", budget_header, cost_header, width = width);
+    for row in rows {
+        let best = match (row.valid, row.best) {
+            (false, _) => "invalid".to_string(),
+            (true, Some(best)) => format!("{:.4}", best),
+            (true, None) => "-".to_string(),
+        };
+        let cost = row.cost.map_or("-".to_string(), |cost| cost.to_string());
+        println!("{:<width$}  {:>12}  {:>12}", row.board, best, cost, width = width);
+    }
+}
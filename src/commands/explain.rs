@@ -0,0 +1,53 @@
+use clap::Clap;
+
+use btv::{challenges::TARGET_SET, explain::explain, solver};
+
+use super::BoardArgs;
+
+/// Arguments of the `explain` command.
+#[derive(Clap)]
+pub struct ExplainArgs {
+    #[clap(flatten)]
+    board: BoardArgs,
+
+    /// The total cost of the abilities to explain the best plan at.
+    #[clap(long)]
+    budget: u32,
+}
+
+pub fn run(args: ExplainArgs) {
+    let board = args.board.load();
+
+    eprintln!("Searching for optimal challenge order");
+    let frontier = board.solver.solve_from(&board.challenges, &board.state, &board.constraints);
+    let solution = match solver::best_within(&frontier, args.budget) {
+        Some(solution) => solution,
+        None => {
+            println!("No plan costs at most {} points", args.budget);
+            return;
+        }
+    };
+
+    println!();
+    println!("Cost: {}", solution.cost);
+    println!("Success chance: {:.4}", solution.log_proba.exp());
+    let explanations = explain(&board.challenges, &board.state, solution);
+    for (step, explanation) in explanations.iter().enumerate() {
+        let challenge = &board.challenges[explanation.challenge];
+        println!();
+        println!(
+            "{}. {} (target {}, max abilities {})",
+            step + 1,
+            challenge.name,
+            TARGET_SET[challenge.target_idx],
+            challenge.abilities
+        );
+        println!("   Dice: {}", explanation.abilities);
+        println!("   Active rewards: {}", explanation.rewards);
+        println!(
+            "   Pass chance: {:.4}, expected roll: {:.1}, margin: {:+.1}",
+            explanation.proba, explanation.expected, explanation.margin
+        );
+        println!("   Reward: {}", challenge.reward);
+    }
+}
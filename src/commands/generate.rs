@@ -0,0 +1,56 @@
+use std::{fs, path::Path};
+
+use clap::Clap;
+use rand::{rngs::StdRng, SeedableRng};
+
+use btv::generator;
+
+/// Arguments of the `generate` command.
+#[derive(Clap)]
+pub struct GenerateArgs {
+    /// The seed of the random number generator.
+    #[clap(long, default_value = "0")]
+    seed: u64,
+
+    /// How hard the boards are, from 0 to 1. Boards at 0.5 are similar to the game's.
+    #[clap(long, default_value = "0.5")]
+    difficulty: f32,
+
+    /// The number of challenges on each board.
+    #[clap(long, default_value = "11")]
+    challenges: usize,
+
+    /// The number of boards to generate. More than one board requires `--dir`.
+    #[clap(long, default_value = "1")]
+    count: usize,
+
+    /// The directory to write the boards to, as `board-N.txt`. Defaults to printing the board.
+    #[clap(long)]
+    dir: Option<String>,
+}
+
+pub fn run(args: GenerateArgs) {
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let dir = match &args.dir {
+        Some(dir) => Path::new(dir),
+        None if args.count > 1 => {
+            eprintln!("Pass --dir to generate more than one board");
+            return;
+        }
+        None => {
+            // Print the board, if any.
+            for _ in 0..args.count {
+                print!("{}", generator::generate(&mut rng, args.challenges, args.difficulty));
+            }
+            return;
+        }
+    };
+    fs::create_dir_all(dir).unwrap();
+    let width = (args.count.max(2) - 1).to_string().len();
+    for idx in 0..args.count {
+        let board = generator::generate(&mut rng, args.challenges, args.difficulty);
+        let path = dir.join(format!("board-{:0width$}.txt", idx, width = width));
+        fs::write(path, board).unwrap();
+    }
+    eprintln!("Wrote {} boards to {}", args.count, dir.display());
+}
@@ -0,0 +1,381 @@
+use std::{
+    fs,
+    io::{self, ErrorKind, Read},
+    num::{ParseFloatError, ParseIntError},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+};
+
+use clap::Clap;
+
+use btv::{
+    challenges::Challenge,
+    constraints::Constraint,
+    parser,
+    solver::{self, SolverConfig},
+    state::GameState,
+    Solver,
+};
+
+pub mod analyze;
+pub mod batch;
+pub mod explain;
+pub mod generate;
+pub mod play;
+pub mod plot;
+pub mod report;
+pub mod research;
+pub mod serve;
+pub mod simulate;
+pub mod solve;
+pub mod stdio;
+pub mod watch;
+
+/// Arguments describing the board to solve.
+#[derive(Clap)]
+pub struct BoardArgs {
+    /// The path of the input file, or `-` to read it from stdin.
+    input: String,
+
+    /// The path of a file describing a game in progress. Only the remaining challenges are
+    /// solved, using the owned dice and the points left.
+    #[clap(long)]
+    state: Option<String>,
+
+    /// The path of a file with constraints on the challenge order.
+    #[clap(long)]
+    constraints: Option<String>,
+
+    #[clap(flatten)]
+    solver: SolverArgs,
+}
+
+/// Arguments configuring the solver.
+#[derive(Clap)]
+pub struct SolverArgs {
+    /// The path of the challenge setup cache. Defaults to a file in the temporary directory.
+    #[clap(long)]
+    cache: Option<String>,
+
+    /// Recompute the challenge setups without reading or writing the cache.
+    #[clap(long)]
+    no_cache: bool,
+
+    /// Ignore individual challenge setups with a success chance of at most this value. Lower it
+    /// for hard boards. Defaults to 0.1.
+    #[clap(long, parse(try_from_str = parse_min_proba))]
+    min_proba: Option<f32>,
+
+    /// The maximum cost to spend on a single challenge. Defaults to 300.
+    #[clap(long)]
+    max_challenge_cost: Option<u32>,
+
+    /// The maximum cost to spend on all challenges. Defaults to 1200.
+    #[clap(long)]
+    max_total_cost: Option<u32>,
+
+    /// The maximum number of abilities on a single challenge. Defaults to 16.
+    #[clap(long, parse(try_from_str = parse_max_abilities))]
+    max_abilities: Option<usize>,
+
+    /// The number of roll totals tracked when computing success chances. Must be larger than
+    /// every target. Defaults to 71.
+    #[clap(long, parse(try_from_str = parse_precision))]
+    precision: Option<usize>,
+}
+
+/// A parsed board, along with the precomputed challenge setups.
+pub struct Board {
+    pub challenges: Vec<Challenge>,
+    pub state: GameState,
+    pub constraints: Vec<Constraint>,
+    pub solver: Solver,
+}
+
+/// The challenges, game state and constraints read from the input files.
+pub type ParsedBoard = (Vec<Challenge>, GameState, Vec<Constraint>);
+
+impl BoardArgs {
+    /// Parses the input files and loads the challenge setups.
+    pub fn load(&self) -> Board {
+        let (challenges, state, constraints) = self.parse();
+        Board {
+            challenges,
+            state,
+            constraints,
+            solver: self.solver.build(),
+        }
+    }
+
+    /// Parses the input files, returning the challenges, the game state and the constraints.
+    ///
+    /// Exits with the error if a file cannot be read or parsed.
+    pub fn parse(&self) -> ParsedBoard {
+        let text = read_file_or_exit(&self.input);
+        self.parse_text(&text).unwrap_or_else(|message| {
+            clap::Error::with_description(message + "\n", clap::ErrorKind::InvalidValue).exit()
+        })
+    }
+
+    /// Parses the text of the input file along with the state and constraints files, returning
+    /// the challenges, the game state and the constraints.
+    ///
+    /// Returns an error message if the state or constraints file cannot be read, or if a file
+    /// cannot be parsed.
+    pub fn parse_text(&self, text: &str) -> Result<ParsedBoard, String> {
+        let read =
+            |path: &str| read_file(path).map_err(|err| format!("Cannot read {}: {}", path, err));
+        let invalid = |path: &str, err| format!("Cannot parse {}: {}", path, err);
+        eprintln!("Parsing challenges");
+        let challenges = parser::parse(text).map_err(|err| invalid(&self.input, err))?;
+        let state = match &self.state {
+            Some(path) => {
+                eprintln!("Parsing game state");
+                parser::parse_state(read(path)?, &challenges).map_err(|err| invalid(path, err))?
+            }
+            None => GameState::new(),
+        };
+        let constraints = match &self.constraints {
+            Some(path) => {
+                eprintln!("Parsing constraints");
+                parser::parse_constraints(read(path)?, &challenges)
+                    .map_err(|err| invalid(path, err))?
+            }
+            None => Vec::new(),
+        };
+        if !solver::splits_owned_dice_exactly(&challenges, &state, &constraints) {
+            eprintln!(
+                "The owned dice can be split between the challenges in too many ways, so they are \
+                 split approximately and the plans using them may pass less often than the best \
+                 plan"
+            );
+        }
+        Ok((challenges, state, constraints))
+    }
+}
+
+impl SolverArgs {
+    /// Loads the challenge setups and returns the solver.
+    pub fn build(&self) -> Solver {
+        self.build_with(self.config())
+    }
+
+    /// Loads the challenge setups of another config and returns the solver, using the cache
+    /// options.
+    ///
+    /// Exits with the error if the config is invalid.
+    pub fn build_with(&self, config: SolverConfig) -> Solver {
+        let mut builder = Solver::builder().config(config);
+        if let Some(path) = &self.cache {
+            builder = builder.cache_path(path);
+        }
+        if self.no_cache {
+            builder = builder.no_cache();
+        }
+        builder.build().unwrap_or_else(|message| {
+            let message = format!("Invalid solver options: {}\n", message);
+            clap::Error::with_description(message, clap::ErrorKind::InvalidValue).exit()
+        })
+    }
+
+    /// Returns the solver config, with the defaults replaced by the given options.
+    pub fn config(&self) -> SolverConfig {
+        let default = SolverConfig::default();
+        SolverConfig {
+            max_cost: self.max_challenge_cost.unwrap_or(default.max_cost),
+            max_total_cost: self.max_total_cost.unwrap_or(default.max_total_cost),
+            max_abilities: self.max_abilities.unwrap_or(default.max_abilities),
+            min_proba: self.min_proba.unwrap_or(default.min_proba),
+            precision: self.precision.unwrap_or(default.precision),
+        }
+    }
+}
+
+/// Parses the ability cap of the solver, checking it like `SolverConfig::validate`.
+fn parse_max_abilities(text: &str) -> Result<usize, String> {
+    let max_abilities = text.parse().map_err(|error: ParseIntError| error.to_string())?;
+    SolverConfig { max_abilities, ..SolverConfig::default() }.validate()?;
+    Ok(max_abilities)
+}
+
+/// Parses the setup threshold of the solver, checking it like `SolverConfig::validate`.
+fn parse_min_proba(text: &str) -> Result<f32, String> {
+    let min_proba = text.parse().map_err(|error: ParseFloatError| error.to_string())?;
+    SolverConfig { min_proba, ..SolverConfig::default() }.validate()?;
+    Ok(min_proba)
+}
+
+/// Parses the precision of the solver, checking it like `SolverConfig::validate`.
+fn parse_precision(text: &str) -> Result<usize, String> {
+    let precision = text.parse().map_err(|error: ParseIntError| error.to_string())?;
+    SolverConfig { precision, ..SolverConfig::default() }.validate()?;
+    Ok(precision)
+}
+
+/// The most distinct plans kept at each budget, so that one request cannot exhaust the memory.
+const MAX_TOP_K: usize = 100;
+
+/// Checks the number of distinct plans to keep at each budget.
+pub(super) fn check_top_k(top_k: usize) -> Result<usize, String> {
+    if top_k == 0 || top_k > MAX_TOP_K {
+        return Err(format!("Keep between 1 and {} plans at each budget", MAX_TOP_K));
+    }
+    Ok(top_k)
+}
+
+/// Parses the number of distinct plans to keep at each budget, checking it like `check_top_k`.
+fn parse_top_k(text: &str) -> Result<usize, String> {
+    check_top_k(text.parse().map_err(|error: ParseIntError| error.to_string())?)
+}
+
+/// Whether stdin was read by `read_file`.
+static STDIN_READ: AtomicBool = AtomicBool::new(false);
+
+/// Reads the contents of the file at the given path, or of stdin if the path is `-`.
+///
+/// Stdin can only be read once, so passing `-` for a second file is an error.
+pub(super) fn read_file(path: &str) -> io::Result<String> {
+    if path != "-" {
+        return fs::read_to_string(path);
+    }
+    if STDIN_READ.swap(true, Ordering::Relaxed) {
+        let message = "stdin was already read, pass `-` for a single file";
+        return Err(io::Error::new(ErrorKind::InvalidInput, message));
+    }
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    Ok(input)
+}
+
+/// Reads a file like `read_file`, exiting with the error if it cannot be read.
+pub(super) fn read_file_or_exit(path: &str) -> String {
+    read_file(path).unwrap_or_else(|err| {
+        let message = format!("Cannot read {}: {}\n", path, err);
+        clap::Error::with_description(message, clap::ErrorKind::Io).exit()
+    })
+}
+
+/// Returns the contents parsed from a file, exiting with the error if they could not be parsed.
+pub(super) fn parsed_or_exit<T>(path: &str, parsed: Result<T, String>) -> T {
+    parsed.unwrap_or_else(|err| {
+        let message = format!("Cannot parse {}: {}\n", path, err);
+        clap::Error::with_description(message, clap::ErrorKind::InvalidValue).exit()
+    })
+}
+
+/// Returns the paths of the boards to read, listing the files of every directory in order.
+///
+/// Exits with the error if a directory cannot be listed.
+pub(super) fn board_paths(inputs: &[String]) -> Vec<String> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        let path = PathBuf::from(input);
+        if path.is_dir() {
+            let files = dir_files(&path).unwrap_or_else(|err| {
+                let message = format!("Cannot list {}: {}\n", input, err);
+                clap::Error::with_description(message, clap::ErrorKind::Io).exit()
+            });
+            paths.extend(files.iter().map(|path| path.to_string_lossy().into_owned()));
+        } else {
+            paths.push(input.clone());
+        }
+    }
+    paths
+}
+
+/// Returns the paths of the files in a directory, sorted.
+fn dir_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Applies a function to every item, spreading the work across the available cores.
+fn parallel_map<T: Sync, U: Send, F: Fn(&T) -> U + Sync>(items: &[T], f: F) -> Vec<U> {
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, U)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers.min(items.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut output = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(idx) {
+                            Some(item) => output.push((idx, f(item))),
+                            None => return output,
+                        }
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+    });
+    results.sort_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::sync::OnceLock;
+
+    use super::*;
+
+    /// A board with two challenges, one of them offering a reward, in the JSON format.
+    pub const BOARD: &str = r#"{"challenges": [
+        {"name": "Monologue", "target": 20, "abilities": 4, "reward": "+1 Diction Range"},
+        {"name": "Quickteams", "target": 10, "abilities": 3, "reward": "None"}
+    ]}"#;
+
+    /// Returns the challenges and state of `BOARD`.
+    pub fn board() -> (Vec<Challenge>, GameState) {
+        let board: btv::json::Board = serde_json::from_str(BOARD).unwrap();
+        board.parse().unwrap()
+    }
+
+    /// Returns a solver with a low cost cap, so that its challenge setups are quick to compute.
+    pub fn solver() -> &'static Solver {
+        static SOLVER: OnceLock<Solver> = OnceLock::new();
+        SOLVER.get_or_init(|| {
+            let config = SolverConfig {
+                max_cost: 40,
+                max_total_cost: 80,
+                ..SolverConfig::default()
+            };
+            Solver::builder().no_cache().config(config).build().unwrap()
+        })
+    }
+
+    #[test]
+    fn test_board_paths() {
+        let dir = std::env::temp_dir().join(format!("btv-test-boards-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("b.json"), BOARD).unwrap();
+        fs::write(dir.join("a.txt"), "").unwrap();
+        let input = dir.to_string_lossy().into_owned();
+        let inputs = [input.clone(), "missing.json".to_string(), "-".to_string()];
+        let paths = board_paths(&inputs);
+        assert!(dir_files(&dir.join("missing")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let expected = [dir.join("a.txt"), dir.join("b.json")];
+        let expected = expected.iter().map(|path| path.to_string_lossy().into_owned());
+        let expected: Vec<String> = expected.chain(inputs[1..].iter().cloned()).collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn test_read_file() {
+        assert!(read_file("missing.json").is_err());
+        // The test runner does not provide stdin, so only the second read is checked.
+        STDIN_READ.store(true, Ordering::Relaxed);
+        assert_eq!(read_file("-").unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,156 @@
+use std::io::{self, BufRead, Write};
+
+use clap::Clap;
+
+use btv::{
+    abilities::Ability,
+    challenges::{Challenge, TARGET_SET},
+    constraints::Constraint,
+    solver::SolutionVertex,
+    state::GameState,
+};
+
+use super::{Board, BoardArgs};
+
+/// Arguments of the `play` command.
+#[derive(Clap)]
+pub struct PlayArgs {
+    #[clap(flatten)]
+    board: BoardArgs,
+
+    /// The points to buy abilities with. Defaults to the points of the state file, or an unlimited
+    /// budget.
+    #[clap(long)]
+    budget: Option<u32>,
+}
+
+const HELP: &str = "Commands:
+  roll <total>       The dice of the next challenge rolled this total
+  pass               The next challenge was passed
+  fail               The next challenge was failed
+  refund <ability>   A die was refunded, e.g. `refund Diction`
+  status             Print the dice, points and rewards
+  help               Print this message
+  quit               Stop playing";
+
+pub fn run(args: PlayArgs) {
+    let Board {
+        challenges,
+        mut state,
+        mut constraints,
+        solver,
+    } = args.board.load();
+    if args.budget.is_some() {
+        state.points = args.budget;
+    }
+
+    println!("{}", HELP);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if (0..challenges.len()).all(|idx| {
+            state.is_completed(idx) || constraints.contains(&Constraint::Exclude { challenge: idx })
+        }) {
+            println!();
+            println!("All challenges passed");
+            print_status(&challenges, &state);
+            return;
+        }
+
+        // Re-plan from the current state.
+        let plan = solver.solve_from(&challenges, &state, &constraints).pop();
+        println!();
+        match &plan {
+            Some(plan) => print_next(&challenges, &state, plan),
+            None => println!("No plan can pass the remaining challenges"),
+        }
+
+        // Apply the next event.
+        print!("> ");
+        io::stdout().flush().unwrap();
+        let line = match lines.next() {
+            Some(line) => line.unwrap(),
+            None => return,
+        };
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let argument = words.collect::<Vec<_>>().join(" ");
+        let passed = match (command, &plan) {
+            ("roll", Some(plan)) => match argument.parse::<u32>() {
+                Ok(total) => Some(total >= TARGET_SET[challenges[plan.order[0]].target_idx] as u32),
+                Err(_) => {
+                    println!("Expected the roll total, e.g. `roll 34`");
+                    None
+                }
+            },
+            ("pass", Some(_)) => Some(true),
+            ("fail", Some(_)) => Some(false),
+            ("roll" | "pass" | "fail", None) => {
+                println!("There is no next challenge to attempt");
+                None
+            }
+            ("refund", _) => {
+                match argument.parse::<Ability>() {
+                    Ok(ability) => state.refund(ability),
+                    Err(_) => println!("Unknown ability {:?}", argument),
+                }
+                None
+            }
+            ("status", _) => {
+                print_status(&challenges, &state);
+                None
+            }
+            ("quit", _) => return,
+            _ => {
+                println!("{}", HELP);
+                None
+            }
+        };
+
+        if let (Some(passed), Some(plan)) = (passed, &plan) {
+            let idx = plan.order[0];
+            if !state.spend(&plan.abilities[0]) {
+                println!(
+                    "Cannot afford {} with the owned dice and the points left, the attempt was not \
+                     recorded",
+                    plan.abilities[0]
+                );
+                continue;
+            }
+            if passed {
+                println!("Passed {}, earning {}", challenges[idx].name, challenges[idx].reward);
+                state.completed.push(idx);
+                constraints = constraints.iter().filter_map(|c| c.after_completing(idx)).collect();
+            } else {
+                println!("Failed {}", challenges[idx].name);
+            }
+        }
+    }
+}
+
+/// Prints the next challenge of the plan and the odds of passing every remaining challenge.
+fn print_next(challenges: &[Challenge], state: &GameState, plan: &SolutionVertex) {
+    let challenge = &challenges[plan.order[0]];
+    println!(
+        "Next: {} (target {}, max abilities {})",
+        challenge.name, TARGET_SET[challenge.target_idx], challenge.abilities
+    );
+    println!("Dice: {}", plan.abilities[0]);
+    let to_buy = state.to_buy(&plan.abilities[0]);
+    if to_buy.cost() > 0 {
+        println!("To buy: {} ({} points)", to_buy, to_buy.cost());
+    }
+    println!("Chance to pass every remaining challenge: {:.4}", plan.log_proba.exp());
+    let names: Vec<&str> = plan.order.iter().map(|idx| challenges[*idx].name.as_str()).collect();
+    println!("Remaining order: {}", names.join(", "));
+}
+
+/// Prints the owned dice, the points left and the rewards earned.
+fn print_status(challenges: &[Challenge], state: &GameState) {
+    println!("Dice: {}", state.dice);
+    match state.points {
+        Some(points) => println!("Points: {}", points),
+        None => println!("Points: unlimited"),
+    }
+    println!("Rewards: {}", state.rewards(challenges));
+}
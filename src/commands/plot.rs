@@ -0,0 +1,153 @@
+use std::{fs, path::Path};
+
+use clap::{Clap, Error, ErrorKind};
+
+use btv::{
+    parser,
+    plot::{self, Series},
+    solver::SolverConfig,
+};
+
+use super::{parsed_or_exit, read_file_or_exit, SolverArgs};
+
+/// Arguments of the `plot` command.
+#[derive(Clap)]
+pub struct PlotArgs {
+    /// The paths of the input files.
+    #[clap(required = true)]
+    inputs: Vec<String>,
+
+    /// The path of the SVG file to write. Defaults to `frontier.svg`.
+    #[clap(long)]
+    output: Option<String>,
+
+    /// The budget to mark on the chart.
+    #[clap(long)]
+    budget: Option<u32>,
+
+    /// A ruleset to plot every board under, as comma separated solver options, for example
+    /// `max-abilities=12,min-proba=0.05`. Repeat to compare rulesets.
+    #[clap(long, number_of_values = 1)]
+    ruleset: Vec<String>,
+
+    #[clap(flatten)]
+    solver: SolverArgs,
+}
+
+/// The default path of the chart.
+const OUTPUT: &str = "frontier.svg";
+
+pub fn run(args: PlotArgs) {
+    let rulesets: Vec<(String, SolverConfig)> = if args.ruleset.is_empty() {
+        vec![(String::new(), args.solver.config())]
+    } else {
+        let config = args.solver.config();
+        args.ruleset
+            .iter()
+            .map(|ruleset| match parse_ruleset(ruleset, config) {
+                Ok(config) => (ruleset.clone(), config),
+                Err(err) => {
+                    let message = format!("Invalid ruleset '{}': {}\n", ruleset, err);
+                    Error::with_description(message, ErrorKind::InvalidValue).exit()
+                }
+            })
+            .collect()
+    };
+
+    eprintln!("Parsing challenges");
+    let boards: Vec<(String, _)> = args
+        .inputs
+        .iter()
+        .map(|input| {
+            let challenges = parsed_or_exit(input, parser::parse(read_file_or_exit(input)));
+            (label(input), challenges)
+        })
+        .collect();
+
+    let mut series = Vec::new();
+    for (ruleset, config) in &rulesets {
+        let solver = args.solver.build_with(*config);
+        for (name, challenges) in &boards {
+            eprintln!("Searching for optimal challenge order of {}", name);
+            let label = if ruleset.is_empty() {
+                name.clone()
+            } else {
+                format!("{} ({})", name, ruleset)
+            };
+            series.push(Series::new(label, &solver.solve(challenges)));
+        }
+    }
+
+    let path = args.output.as_deref().unwrap_or(OUTPUT);
+    if let Err(err) = fs::write(path, plot::svg(&series, args.budget)) {
+        let message = format!("Cannot write {}: {}\n", path, err);
+        Error::with_description(message, ErrorKind::Io).exit()
+    }
+    eprintln!("Wrote {}", path);
+}
+
+/// Returns the name of a board in the legend.
+fn label(input: &str) -> String {
+    let stem = Path::new(input).file_stem();
+    stem.map_or(input.to_string(), |stem| stem.to_string_lossy().into_owned())
+}
+
+/// Parses comma separated `option=value` pairs, replacing the options of the config.
+///
+/// Returns a message naming the rejected option if a pair is malformed or the config is invalid.
+fn parse_ruleset(text: &str, mut config: SolverConfig) -> Result<SolverConfig, String> {
+    for pair in text.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (option, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected option=value, found '{}'", pair))?;
+        let (option, value) = (option.trim(), value.trim());
+        let invalid = |err: &dyn ToString| format!("{}: {}", option, err.to_string());
+        match option {
+            "min-proba" => config.min_proba = value.parse().map_err(|e| invalid(&e))?,
+            "max-challenge-cost" => config.max_cost = value.parse().map_err(|e| invalid(&e))?,
+            "max-total-cost" => config.max_total_cost = value.parse().map_err(|e| invalid(&e))?,
+            "max-abilities" => config.max_abilities = value.parse().map_err(|e| invalid(&e))?,
+            "precision" => config.precision = value.parse().map_err(|e| invalid(&e))?,
+            _ => return Err(format!("unknown option '{}'", option)),
+        }
+    }
+    config.validate()?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ruleset() {
+        let default = SolverConfig::default();
+        let config = parse_ruleset(" max-abilities = 12, min-proba=0.05,", default).unwrap();
+        assert_eq!(config.max_abilities, 12);
+        assert_eq!(config.min_proba, 0.05);
+        assert_eq!(config.max_cost, default.max_cost);
+
+        let text = "max-challenge-cost=400,max-total-cost=1600,precision=100";
+        let config = parse_ruleset(text, default).unwrap();
+        assert_eq!((config.max_cost, config.max_total_cost, config.precision), (400, 1600, 100));
+        assert_eq!(parse_ruleset("", default).unwrap(), default);
+    }
+
+    #[test]
+    fn test_parse_ruleset_errors() {
+        let default = SolverConfig::default();
+        let error = parse_ruleset("max-abilities", default).unwrap_err();
+        assert_eq!(error, "expected option=value, found 'max-abilities'");
+        let error = parse_ruleset("speed=2", default).unwrap_err();
+        assert_eq!(error, "unknown option 'speed'");
+        let error = parse_ruleset("max-abilities=many", default).unwrap_err();
+        assert!(error.starts_with("max-abilities: "));
+        assert!(parse_ruleset("precision=50", default).is_err());
+        assert!(parse_ruleset("max-total-cost=100", default).is_err());
+    }
+
+    #[test]
+    fn test_label() {
+        assert_eq!(label("boards/week 3.txt"), "week 3");
+    }
+}
@@ -0,0 +1,351 @@
+use std::{fmt::Write, fs, ops::Add, path::Path};
+
+use clap::{ArgEnum, Clap};
+
+use btv::{
+    abilities::{Abilities, Ability},
+    challenges::TARGET_SET,
+    explain::explain,
+};
+
+use super::BoardArgs;
+
+/// Arguments of the `report` command.
+#[derive(Clap)]
+pub struct ReportArgs {
+    #[clap(flatten)]
+    board: BoardArgs,
+
+    /// The total cost of the abilities to report the best plan at.
+    #[clap(long)]
+    budget: u32,
+
+    /// The format of the report.
+    #[clap(arg_enum, long, default_value = "md")]
+    format: ReportFormat,
+
+    /// The path of the file to write. Defaults to stdout.
+    #[clap(long)]
+    output: Option<String>,
+}
+
+/// The formats a report can be written in.
+#[derive(ArgEnum, Copy, Clone, Eq, PartialEq)]
+pub enum ReportFormat {
+    /// Markdown, with pipe tables.
+    Md,
+    /// A standalone HTML page.
+    Html,
+}
+
+/// The number of frontier points listed on each side of the chosen plan.
+const FRONTIER_CONTEXT: usize = 5;
+
+/// A part of a report, rendered the same way in every format.
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    List(Vec<String>),
+    Bullets(Vec<String>),
+    Table(Vec<String>, Vec<Vec<String>>),
+}
+
+pub fn run(args: ReportArgs) {
+    let board = args.board.load();
+    let (challenges, state) = (&board.challenges, &board.state);
+
+    eprintln!("Searching for optimal challenge order");
+    let frontier = board.solver.solve_from(challenges, state, &board.constraints);
+    let position = match frontier.iter().rposition(|v| v.cost <= args.budget) {
+        Some(position) => position,
+        None => {
+            println!("No plan costs at most {} points", args.budget);
+            return;
+        }
+    };
+    let solution = &frontier[position];
+    let explanations = explain(challenges, state, solution);
+    let totals = solution.abilities.iter().fold(Abilities::new(), Abilities::add);
+    let mut blocks = Vec::new();
+
+    blocks.push(Block::Paragraph(format!(
+        "Best plan within {} points: costs {} points and passes every challenge with a chance of \
+         {:.4}.",
+        args.budget,
+        solution.cost,
+        solution.log_proba.exp()
+    )));
+
+    // The board.
+    blocks.push(Block::Heading("Challenges".to_string()));
+    blocks.push(Block::Table(
+        strings(&["Challenge", "Target", "Max abilities", "Reward", "Status"]),
+        challenges
+            .iter()
+            .enumerate()
+            .map(|(idx, challenge)| {
+                let status = if state.is_completed(idx) { "Passed" } else { "Remaining" };
+                vec![
+                    challenge.name.clone(),
+                    TARGET_SET[challenge.target_idx].to_string(),
+                    challenge.abilities.to_string(),
+                    challenge.reward.to_string(),
+                    status.to_string(),
+                ]
+            })
+            .collect(),
+    ));
+
+    // The order and the dice of each challenge.
+    blocks.push(Block::Heading("Order".to_string()));
+    blocks.push(Block::List(
+        solution.order.iter().map(|idx| challenges[*idx].name.clone()).collect(),
+    ));
+    let used: Vec<Ability> =
+        Ability::values().iter().copied().filter(|ability| totals[*ability] > 0).collect();
+    let mut header = strings(&["#", "Challenge"]);
+    header.extend(used.iter().map(|ability| ability.name().to_string()));
+    header.push("Active rewards".to_string());
+    blocks.push(Block::Heading("Dice".to_string()));
+    blocks.push(Block::Table(
+        header,
+        explanations
+            .iter()
+            .enumerate()
+            .map(|(step, explanation)| {
+                let name = challenges[explanation.challenge].name.clone();
+                let mut row = vec![(step + 1).to_string(), name];
+                row.extend(used.iter().map(|ability| explanation.abilities[*ability].to_string()));
+                row.push(explanation.rewards.to_string());
+                row
+            })
+            .collect(),
+    ));
+    let to_buy = state.to_buy(&totals);
+    blocks.push(Block::Paragraph(format!(
+        "Totals: {}. To buy: {} ({} points).",
+        totals,
+        to_buy,
+        to_buy.cost()
+    )));
+
+    // The chance of passing each challenge, and every challenge up to it.
+    blocks.push(Block::Heading("Probability breakdown".to_string()));
+    let mut cumulative = 1.0;
+    let mut rows = Vec::new();
+    for (step, explanation) in explanations.iter().enumerate() {
+        cumulative *= explanation.proba;
+        rows.push(vec![
+            (step + 1).to_string(),
+            challenges[explanation.challenge].name.clone(),
+            format!("{:.4}", explanation.proba),
+            format!("{:.4}", cumulative),
+            format!("{:.1}", explanation.expected),
+            format!("{:+.1}", explanation.margin),
+        ]);
+    }
+    blocks.push(Block::Table(
+        strings(&["#", "Challenge", "Pass chance", "Passed so far", "Expected roll", "Margin"]),
+        rows,
+    ));
+
+    // The plans just cheaper and just more expensive than the chosen one.
+    blocks.push(Block::Heading("Frontier".to_string()));
+    let start = position.saturating_sub(FRONTIER_CONTEXT);
+    let end = (position + FRONTIER_CONTEXT + 1).min(frontier.len());
+    blocks.push(Block::Table(
+        strings(&["Cost", "Success chance", "Change", ""]),
+        (start..end)
+            .map(|idx| {
+                let proba = frontier[idx].log_proba.exp();
+                vec![
+                    frontier[idx].cost.to_string(),
+                    format!("{:.4}", proba),
+                    format!("{:+.4}", proba - solution.log_proba.exp()),
+                    if idx == position { "Chosen".to_string() } else { String::new() },
+                ]
+            })
+            .collect(),
+    ));
+
+    // What is left after the challenges. The frontier only holds plans within the points left.
+    blocks.push(Block::Heading("After the challenges".to_string()));
+    // Each challenge is attempted once, as with the expected value objective.
+    let expected_passed: f32 = explanations.iter().map(|explanation| explanation.proba).sum();
+    let mut after = vec![
+        format!("Chance of passing every challenge: {:.4}", solution.log_proba.exp()),
+        format!("Expected challenges passed: {:.2} of {}", expected_passed, explanations.len()),
+        format!("Points of the budget left: {}", args.budget - solution.cost),
+    ];
+    if let Some(points) = state.points_left(&totals) {
+        after.push(format!("Points left after buying the dice: {}", points));
+    }
+    let rewards = solution.order.iter().fold(state.rewards(challenges), |rewards, idx| {
+        rewards | challenges[*idx].reward
+    });
+    after.push(format!("Rewards earned: {}", rewards));
+    blocks.push(Block::Bullets(after));
+
+    let title = format!("Plan report: {}", name(&args.board.input));
+    let output = match args.format {
+        ReportFormat::Md => markdown(&title, &blocks),
+        ReportFormat::Html => html(&title, &blocks),
+    };
+    match &args.output {
+        Some(path) => {
+            if let Err(err) = fs::write(path, output) {
+                let message = format!("Cannot write {}: {}\n", path, err);
+                clap::Error::with_description(message, clap::ErrorKind::Io).exit()
+            }
+            eprintln!("Wrote {}", path);
+        }
+        None => print!("{}", output),
+    }
+}
+
+/// Returns the name of a board, from its path.
+fn name(path: &str) -> String {
+    let stem = Path::new(path).file_stem();
+    stem.map_or(path.to_string(), |stem| stem.to_string_lossy().into_owned())
+}
+
+/// Returns owned copies of the strings.
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+/// Renders the report as Markdown.
+fn markdown(title: &str, blocks: &[Block]) -> String {
+    let mut output = format!("# {}\n", title);
+    for block in blocks {
+        output.push('\n');
+        match block {
+            Block::Heading(text) => writeln!(output, "## {}", text).unwrap(),
+            Block::Paragraph(text) => writeln!(output, "{}", text).unwrap(),
+            Block::List(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    writeln!(output, "{}. {}", idx + 1, item).unwrap();
+                }
+            }
+            Block::Bullets(items) => {
+                for item in items {
+                    writeln!(output, "- {}", item).unwrap();
+                }
+            }
+            Block::Table(header, rows) => {
+                let row = |cells: &[String]| {
+                    let cells: Vec<String> = cells.iter().map(|c| c.replace('|', "\\|")).collect();
+                    format!("| {} |\n", cells.join(" | "))
+                };
+                output.push_str(&row(header));
+                output.push_str(&row(&vec!["---".to_string(); header.len()]));
+                for cells in rows {
+                    output.push_str(&row(cells));
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Renders the report as a standalone HTML page.
+fn html(title: &str, blocks: &[Block]) -> String {
+    let mut output = String::new();
+    writeln!(output, "<!DOCTYPE html>").unwrap();
+    writeln!(output, "<html>\n<head>\n<meta charset=\"utf-8\">").unwrap();
+    writeln!(output, "<title>{}</title>", escape(title)).unwrap();
+    writeln!(
+        output,
+        "<style>body {{ font-family: sans-serif; }} table {{ border-collapse: collapse; }} \
+         th, td {{ border: 1px solid #ccc; padding: 2px 8px; }}</style>"
+    )
+    .unwrap();
+    writeln!(output, "</head>\n<body>\n<h1>{}</h1>", escape(title)).unwrap();
+    for block in blocks {
+        match block {
+            Block::Heading(text) => writeln!(output, "<h2>{}</h2>", escape(text)).unwrap(),
+            Block::Paragraph(text) => writeln!(output, "<p>{}</p>", escape(text)).unwrap(),
+            Block::List(items) | Block::Bullets(items) => {
+                let tag = if let Block::List(_) = block { "ol" } else { "ul" };
+                writeln!(output, "<{}>", tag).unwrap();
+                for item in items {
+                    writeln!(output, "<li>{}</li>", escape(item)).unwrap();
+                }
+                writeln!(output, "</{}>", tag).unwrap();
+            }
+            Block::Table(header, rows) => {
+                output.push_str("<table>\n<tr>");
+                for cell in header {
+                    write!(output, "<th>{}</th>", escape(cell)).unwrap();
+                }
+                output.push_str("</tr>\n");
+                for cells in rows {
+                    output.push_str("<tr>");
+                    for cell in cells {
+                        write!(output, "<td>{}</td>", escape(cell)).unwrap();
+                    }
+                    output.push_str("</tr>\n");
+                }
+                output.push_str("</table>\n");
+            }
+        }
+    }
+    output.push_str("</body>\n</html>\n");
+    output
+}
+
+/// Escapes the characters with a special meaning in HTML text.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns blocks of every kind, with characters that need escaping.
+    fn blocks() -> Vec<Block> {
+        vec![
+            Block::Heading("Order".to_string()),
+            Block::Paragraph("Costs <10> & more".to_string()),
+            Block::List(strings(&["First", "Second"])),
+            Block::Bullets(strings(&["Rewards: \"None\""])),
+            Block::Table(strings(&["Challenge", "Reward"]), vec![strings(&["A | B", "<b>"])]),
+        ]
+    }
+
+    #[test]
+    fn test_markdown() {
+        let output = markdown("Plan report: board", &blocks());
+        let expected = "# Plan report: board\n\n\
+                        ## Order\n\n\
+                        Costs <10> & more\n\n\
+                        1. First\n2. Second\n\n\
+                        - Rewards: \"None\"\n\n\
+                        | Challenge | Reward |\n\
+                        | --- | --- |\n\
+                        | A \\| B | <b> |\n";
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_html() {
+        let output = html("Plan <report>", &blocks());
+        assert!(output.starts_with("<!DOCTYPE html>\n"));
+        assert!(output.contains("<title>Plan &lt;report&gt;</title>"));
+        assert!(output.contains("<h2>Order</h2>\n<p>Costs &lt;10&gt; &amp; more</p>\n"));
+        assert!(output.contains("<ol>\n<li>First</li>\n<li>Second</li>\n</ol>\n"));
+        assert!(output.contains("<ul>\n<li>Rewards: &quot;None&quot;</li>\n</ul>\n"));
+        assert!(output.contains(
+            "<table>\n<tr><th>Challenge</th><th>Reward</th></tr>\n\
+             <tr><td>A | B</td><td>&lt;b&gt;</td></tr>\n</table>\n"
+        ));
+        assert!(output.ends_with("</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(name("boards/week 3.txt"), "week 3");
+        assert_eq!(name("-"), "-");
+    }
+}
@@ -0,0 +1,281 @@
+use std::ops::Add;
+
+use clap::Clap;
+use rand::{rngs::StdRng, SeedableRng};
+
+use btv::{
+    abilities::{Abilities, Ability},
+    challenges::Challenge,
+    generator, parser,
+    rewards::Rewards,
+    solver::{self, SolutionVertex},
+};
+
+use super::{board_paths, parallel_map, read_file, SolverArgs};
+
+/// Arguments of the `research` command.
+#[derive(Clap)]
+pub struct ResearchArgs {
+    /// The paths of recorded boards, or of directories holding them. Defaults to generating
+    /// random boards.
+    inputs: Vec<String>,
+
+    /// The number of random boards to generate, without inputs.
+    #[clap(long, default_value = "1000")]
+    boards: usize,
+
+    /// The seed of the random boards.
+    #[clap(long, default_value = "0")]
+    seed: u64,
+
+    /// How hard the random boards are, from 0 to 1.
+    #[clap(long, default_value = "0.5")]
+    difficulty: f32,
+
+    /// A budget to aggregate the best plans at. Repeat to compare budgets. Defaults to 600, 800,
+    /// 1000 and 1200.
+    #[clap(long, number_of_values = 1)]
+    budget: Vec<u32>,
+
+    #[clap(flatten)]
+    solver: SolverArgs,
+}
+
+/// The default budgets to aggregate the best plans at.
+const BUDGETS: [u32; 4] = [600, 800, 1000, 1200];
+
+/// The quantiles of the success chance reported at each budget.
+const QUANTILES: [(&str, f32); 5] =
+    [("P10", 0.1), ("P25", 0.25), ("Median", 0.5), ("P75", 0.75), ("P90", 0.9)];
+
+/// The width of the first column of the tables, fitting the reward descriptions.
+const LABEL_WIDTH: usize = 42;
+
+/// The best plan of a board at one budget.
+struct Sample {
+    /// The success chance of the plan, or 0 without a plan.
+    proba: f32,
+
+    /// The dice used by the plan.
+    dice: Option<Abilities>,
+
+    /// The position in the order of the challenge giving each reward, if the plan attempts it.
+    positions: Vec<Option<usize>>,
+}
+
+impl Sample {
+    /// Returns the sample of the best plan within a budget, if there is one.
+    fn new(
+        challenges: &[Challenge],
+        solution: Option<&SolutionVertex>,
+        rewards: &[(&str, Rewards)],
+    ) -> Sample {
+        match solution {
+            Some(solution) => Sample {
+                proba: solution.log_proba.exp(),
+                dice: Some(solution.abilities.iter().fold(Abilities::new(), Abilities::add)),
+                positions: rewards
+                    .iter()
+                    .map(|(_, reward)| {
+                        let gives = |idx: &usize| challenges[*idx].reward == *reward;
+                        solution.order.iter().position(gives)
+                    })
+                    .collect(),
+            },
+            None => Sample { proba: 0.0, dice: None, positions: vec![None; rewards.len()] },
+        }
+    }
+}
+
+pub fn run(args: ResearchArgs) {
+    // Boards that cannot be read count as invalid.
+    let boards: Vec<Option<String>> = if args.inputs.is_empty() {
+        let mut rng = StdRng::seed_from_u64(args.seed);
+        (0..args.boards)
+            .map(|_| Some(generator::generate(&mut rng, generator::CHALLENGES, args.difficulty)))
+            .collect()
+    } else {
+        board_paths(&args.inputs)
+            .iter()
+            .map(|path| {
+                read_file(path).map_err(|err| eprintln!("Cannot read {}: {}", path, err)).ok()
+            })
+            .collect()
+    };
+    let budgets = if args.budget.is_empty() { BUDGETS.to_vec() } else { args.budget.clone() };
+    let rewards: Vec<(&str, Rewards)> = Rewards::all()
+        .descriptions()
+        .into_iter()
+        .map(|description| (description, description.parse().unwrap()))
+        .collect();
+
+    // The challenge setups are loaded once and shared by every board.
+    let solver = args.solver.build();
+    eprintln!("Solving {} boards", boards.len());
+    let results = parallel_map(&boards, |text| {
+        let challenges = parser::parse(text.as_ref()?).ok();
+        let challenges = challenges.filter(|challenges| !challenges.is_empty())?;
+        let frontier = solver.solve(&challenges);
+        let samples: Vec<Sample> = budgets
+            .iter()
+            .map(|budget| {
+                Sample::new(&challenges, solver::best_within(&frontier, *budget), &rewards)
+            })
+            .collect();
+        Some(samples)
+    });
+    let invalid = results.iter().filter(|samples| samples.is_none()).count();
+    let results: Vec<Vec<Sample>> = results.into_iter().flatten().collect();
+    println!("Boards: {} ({} invalid)", results.len(), invalid);
+    if results.is_empty() {
+        return;
+    }
+
+    // How often each ability is bought, and how many dice of it.
+    println!();
+    println!("Abilities bought (share of plans, mean dice)");
+    print_header("Ability", &budgets);
+    for ability in Ability::values().iter() {
+        let cells = (0..budgets.len()).map(|b| ability_cell(&results, b, *ability));
+        print_row(ability.name(), cells);
+    }
+
+    // Where the reward challenges go in the order.
+    println!();
+    println!("Reward challenges (mean position, share of plans attempting it before the others)");
+    print_header("Reward", &budgets);
+    for (r, (description, _)) in rewards.iter().enumerate() {
+        let cells = (0..budgets.len()).map(|b| reward_cell(&results, b, r));
+        print_row(description, cells);
+    }
+
+    // The distribution of the chance of passing every challenge.
+    println!();
+    println!("Success chance of the best plan (0 without a plan)");
+    print!("{:<8}  {:>8}", "Budget", "Mean");
+    for (name, _) in QUANTILES.iter() {
+        print!("  {:>8}", name);
+    }
+    println!("  {:>8}", "No plan");
+    for (b, budget) in budgets.iter().enumerate() {
+        let mut probas: Vec<f32> = results.iter().map(|samples| samples[b].proba).collect();
+        probas.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mean = probas.iter().sum::<f32>() / probas.len() as f32;
+        print!("{:<8}  {:>8.4}", budget, mean);
+        for (_, quantile) in QUANTILES.iter() {
+            let idx = ((probas.len() - 1) as f32 * quantile).round() as usize;
+            print!("  {:>8.4}", probas[idx]);
+        }
+        let missing = results.iter().filter(|samples| samples[b].dice.is_none()).count();
+        println!("  {:>8}", percent(missing, results.len()));
+    }
+}
+
+/// Returns the share of the plans at a budget buying an ability, and their mean dice of it.
+fn ability_cell(results: &[Vec<Sample>], b: usize, ability: Ability) -> String {
+    let dice: Vec<u8> =
+        results.iter().filter_map(|samples| samples[b].dice).map(|dice| dice[ability]).collect();
+    let bought = dice.iter().filter(|count| **count > 0).count();
+    let total: u32 = dice.iter().map(|count| *count as u32).sum();
+    let mean = total as f32 / dice.len().max(1) as f32;
+    format!("{} {:>5.2}", percent(bought, dice.len()), mean)
+}
+
+/// Returns the mean position of a reward challenge in the plans at a budget, and the share of
+/// the plans attempting it before the other rewards.
+fn reward_cell(results: &[Vec<Sample>], b: usize, r: usize) -> String {
+    let plans = results.iter().filter(|samples| samples[b].dice.is_some()).count();
+    let positions: Vec<usize> =
+        results.iter().filter_map(|samples| samples[b].positions[r]).collect();
+    let first =
+        results.iter().filter(|samples| first_reward(&samples[b].positions) == Some(r)).count();
+    let total: usize = positions.iter().map(|p| p + 1).sum();
+    let mean = total as f32 / positions.len().max(1) as f32;
+    format!("{:>5.2} {}", mean, percent(first, plans))
+}
+
+/// Returns the index of the reward whose challenge comes first in the order.
+fn first_reward(positions: &[Option<usize>]) -> Option<usize> {
+    positions
+        .iter()
+        .enumerate()
+        .filter_map(|(r, position)| position.map(|position| (position, r)))
+        .min()
+        .map(|(_, r)| r)
+}
+
+/// Formats a share as a percentage.
+fn percent(count: usize, total: usize) -> String {
+    format!("{:>3.0}%", 100.0 * count as f32 / total.max(1) as f32)
+}
+
+/// Prints the header of a table with one column per budget.
+fn print_header(label: &str, budgets: &[u32]) {
+    print!("{:<width$}", label, width = LABEL_WIDTH);
+    for budget in budgets {
+        print!("  {:>11}", budget);
+    }
+    println!();
+}
+
+/// Prints a row of a table with one column per budget.
+fn print_row<I: Iterator<Item = String>>(label: &str, cells: I) {
+    print!("{:<width$}", label, width = LABEL_WIDTH);
+    for cell in cells {
+        print!("  {:>11}", cell);
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::*, *};
+
+    fn sample(dice: Option<Abilities>, positions: Vec<Option<usize>>) -> Sample {
+        Sample { proba: 0.0, dice, positions }
+    }
+
+    #[test]
+    fn test_first_reward() {
+        assert_eq!(first_reward(&[]), None);
+        assert_eq!(first_reward(&[None, None]), None);
+        assert_eq!(first_reward(&[Some(2), None, Some(1)]), Some(2));
+        assert_eq!(first_reward(&[Some(0), Some(3)]), Some(0));
+    }
+
+    #[test]
+    fn test_sample() {
+        let (challenges, _) = board();
+        let rewards = [("+1 Diction Range", Rewards::DICTION_RANGE), ("", Rewards::NONE)];
+        let frontier = solver().solve(&challenges);
+        let solution = solver::best_within(&frontier, 60).unwrap();
+        let sample = Sample::new(&challenges, Some(solution), &rewards);
+        assert_eq!(sample.proba, solution.log_proba.exp());
+        assert_eq!(sample.dice.unwrap().cost(), solution.cost);
+        let position = |name| solution.order.iter().position(|idx| challenges[*idx].name == name);
+        assert_eq!(sample.positions, vec![position("Monologue"), position("Quickteams")]);
+
+        let sample = Sample::new(&challenges, None, &rewards);
+        assert_eq!(sample.proba, 0.0);
+        assert_eq!(sample.dice, None);
+        assert_eq!(sample.positions, vec![None, None]);
+    }
+
+    #[test]
+    fn test_cells() {
+        let mut dice = Abilities::new();
+        dice[Ability::Diction] = 3;
+        let results = vec![
+            vec![sample(Some(dice), vec![Some(1), Some(0)])],
+            vec![sample(Some(Abilities::new()), vec![Some(0), None])],
+            vec![sample(None, vec![None, None])],
+        ];
+        // Plans without dice are left out of the shares and means.
+        assert_eq!(ability_cell(&results, 0, Ability::Diction), " 50%  1.50");
+        assert_eq!(ability_cell(&results, 0, Ability::Timing), "  0%  0.00");
+        assert_eq!(reward_cell(&results, 0, 0), " 1.50  50%");
+        assert_eq!(reward_cell(&results, 0, 1), " 1.00  50%");
+        assert_eq!(ability_cell(&results[2..], 0, Ability::Diction), "  0%  0.00");
+        assert_eq!(reward_cell(&results[2..], 0, 0), " 0.00   0%");
+    }
+}
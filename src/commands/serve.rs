@@ -0,0 +1,360 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+
+use clap::Clap;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use btv::{
+    explain::explain,
+    json,
+    simulator::simulate,
+    solver::{self, SolutionVertex},
+    Challenge, GameState, Rewards, Solver,
+};
+
+use super::{analyze::sensitivity, check_top_k, stdio, SolverArgs};
+
+/// Arguments of the `serve` command.
+#[derive(Clap)]
+pub struct ServeArgs {
+    #[clap(flatten)]
+    solver: SolverArgs,
+
+    /// The port to listen on. Defaults to 8080.
+    #[clap(long)]
+    port: Option<u16>,
+
+    /// Read line-delimited JSON requests from stdin instead of listening for HTTP requests.
+    #[clap(long)]
+    stdio: bool,
+
+    /// The origin allowed to send cross-origin requests, such as `http://localhost:3000`.
+    /// Defaults to rejecting cross-origin requests from web pages.
+    #[clap(long)]
+    cors: Option<String>,
+}
+
+/// The default port of the server.
+const PORT: u16 = 8080;
+
+/// The largest request body to accept, in bytes.
+const MAX_BODY: usize = 1 << 20;
+
+/// The largest request line and headers to accept, in bytes.
+const MAX_HEAD: u64 = 8192;
+
+/// The most challenges with rewards left on a board, as on a full board. The solver tries every
+/// order of these challenges, so one request cannot hold the server for long.
+const MAX_REWARDS: usize = 11;
+
+/// The default number of games played by `/simulate`.
+const RUNS: u32 = 100000;
+
+/// The most games played by `/simulate`, so that one request cannot hold the server for long.
+const MAX_RUNS: u32 = 1000000;
+
+/// A request to one of the endpoints.
+#[derive(Deserialize)]
+struct Request {
+    #[serde(flatten)]
+    board: json::Board,
+
+    /// The budget of `/analyze` and `/simulate`.
+    budget: Option<u32>,
+
+    /// The number of distinct plans per budget of `/solve`.
+    top_k: Option<usize>,
+
+    /// The number of games played by `/simulate`.
+    runs: Option<u32>,
+
+    /// The seed of `/simulate`.
+    seed: Option<u64>,
+}
+
+/// A failed request, along with the HTTP status to respond with.
+struct Error {
+    status: &'static str,
+    message: String,
+}
+
+impl Error {
+    fn bad_request<T: ToString>(message: T) -> Error {
+        Error {
+            status: "400 Bad Request",
+            message: message.to_string(),
+        }
+    }
+}
+
+pub fn run(args: ServeArgs) {
+    let solver = args.solver.build();
+    if args.stdio {
+        stdio::run(solver);
+        return;
+    }
+    let address = ("127.0.0.1", args.port.unwrap_or(PORT));
+    let listener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(err) => clap::Error::with_description(
+            format!("Cannot listen on port {}: {}\n", address.1, err),
+            clap::ErrorKind::Io,
+        )
+        .exit(),
+    };
+    eprintln!("Listening on http://{}", listener.local_addr().unwrap());
+
+    // Requests are handled one at a time, sharing the challenge setups of the solver.
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(err) = handle(&solver, args.cors.as_deref(), stream) {
+                    eprintln!("Connection failed: {}", err);
+                }
+            }
+            Err(err) => eprintln!("Connection failed: {}", err),
+        }
+    }
+}
+
+/// Reads a single request from the connection and writes the response, allowing cross-origin
+/// requests from `cors`.
+fn handle(solver: &Solver, cors: Option<&str>, mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = BufReader::new(&stream);
+
+    // Read the request line and headers, up to `MAX_HEAD` bytes.
+    let mut head = (&mut reader).take(MAX_HEAD);
+    let mut line = String::new();
+    head.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("").to_string();
+    let mut content_length = Some(0);
+    loop {
+        let mut header = String::new();
+        if head.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok();
+            }
+        }
+    }
+    let head_too_large = head.limit() == 0;
+
+    let response = if head_too_large {
+        Err(Error {
+            status: "431 Request Header Fields Too Large",
+            message: format!("The headers are larger than {} bytes", MAX_HEAD),
+        })
+    } else if method == "OPTIONS" {
+        Ok(None)
+    } else if method != "POST" {
+        Err(Error {
+            status: "405 Method Not Allowed",
+            message: format!("{} is not supported, use POST", method),
+        })
+    } else {
+        match content_length {
+            None => Err(Error::bad_request("Invalid Content-Length")),
+            Some(content_length) if content_length > MAX_BODY => Err(Error {
+                status: "413 Payload Too Large",
+                message: format!("The body is larger than {} bytes", MAX_BODY),
+            }),
+            Some(content_length) => {
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body)?;
+                respond(solver, &path, &body).map(Some)
+            }
+        }
+    };
+
+    let (status, body) = match response {
+        Ok(Some(body)) => ("200 OK", body),
+        Ok(None) => ("204 No Content", String::new()),
+        Err(err) => (err.status, json!({ "error": err.message }).to_string()),
+    };
+    eprintln!("{} {} {}", method, path, status);
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: application/json\r\n", status)?;
+    if let Some(origin) = cors {
+        write!(
+            stream,
+            "Access-Control-Allow-Origin: {}\r\n\
+             Access-Control-Allow-Methods: POST, OPTIONS\r\n\
+             Access-Control-Allow-Headers: Content-Type\r\n",
+            origin
+        )?;
+    }
+    write!(stream, "Content-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)?;
+    stream.flush()
+}
+
+/// Returns the response body of a request to an endpoint.
+fn respond(solver: &Solver, path: &str, body: &[u8]) -> Result<String, Error> {
+    let request: Request = serde_json::from_slice(body).map_err(Error::bad_request)?;
+    let (challenges, state) = request
+        .board
+        .parse()
+        .ok_or_else(|| Error::bad_request("Invalid board"))?;
+    let rewards = (0..challenges.len())
+        .filter(|idx| !state.completed.contains(idx) && challenges[*idx].reward != Rewards::NONE)
+        .count();
+    if rewards > MAX_REWARDS {
+        return Err(Error::bad_request(format!(
+            "Leave at most {} challenges with rewards",
+            MAX_REWARDS
+        )));
+    }
+    let budget = || request.budget.ok_or_else(|| Error::bad_request("Missing budget"));
+
+    match path {
+        "/solve" => {
+            let top_k = check_top_k(request.top_k.unwrap_or(1)).map_err(Error::bad_request)?;
+            let frontier = solver.solve_top_k(&challenges, &state, &[], top_k);
+            let output = json::Frontier::new(&challenges, &frontier);
+            Ok(serde_json::to_string(&output).unwrap())
+        }
+        "/analyze" => {
+            let rows = sensitivity(solver, &challenges, &state, &[], budget()?);
+            let baseline = rows[0].1;
+            let rows: Vec<Value> = rows[1..]
+                .iter()
+                .map(|(name, proba)| {
+                    json!({ "name": name, "probability": proba, "change": proba - baseline })
+                })
+                .collect();
+            Ok(json!({ "baseline": baseline, "perturbations": rows }).to_string())
+        }
+        "/simulate" => {
+            let frontier = solver.solve_from(&challenges, &state, &[]);
+            let solution = solver::best_within(&frontier, budget()?)
+                .ok_or_else(|| Error::bad_request("No plan costs at most the budget"))?;
+            let runs = request.runs.unwrap_or(RUNS);
+            if runs > MAX_RUNS {
+                return Err(Error::bad_request(format!("Play at most {} runs", MAX_RUNS)));
+            }
+            let seed = request.seed.unwrap_or(0);
+            Ok(simulation(&challenges, &state, solution, runs, seed).to_string())
+        }
+        _ => Err(Error {
+            status: "404 Not Found",
+            message: format!("Unknown endpoint {}", path),
+        }),
+    }
+}
+
+/// Plays the plan and returns the predicted and simulated success chances.
+fn simulation(
+    challenges: &[Challenge],
+    state: &GameState,
+    solution: &SolutionVertex,
+    runs: u32,
+    seed: u64,
+) -> Value {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let simulation = simulate(challenges, state, solution, runs, &mut rng);
+    let (low, high) = simulation.confidence_interval(1.96);
+    let steps: Vec<Value> = explain(challenges, state, solution)
+        .iter()
+        .enumerate()
+        .map(|(step, explanation)| {
+            json!({
+                "name": challenges[explanation.challenge].name,
+                "predicted": explanation.proba,
+                "attempts": simulation.attempts[step],
+                "passes": simulation.challenge_passes[step],
+            })
+        })
+        .collect();
+    json!({
+        "plan": json::Plan::new(challenges, solution),
+        "predicted": solution.log_proba.exp(),
+        "simulated": simulation.pass_rate(),
+        "low": low,
+        "high": high,
+        "runs": simulation.runs,
+        "challenges": steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{super::tests::*, *};
+
+    /// Sends a raw request to `handle` and returns the raw response.
+    fn request(raw: &str) -> String {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let raw = raw.to_string();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream.write_all(raw.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let (stream, _) = listener.accept().unwrap();
+        handle(solver(), Some("http://localhost:3000"), stream).unwrap();
+        client.join().unwrap()
+    }
+
+    /// Returns a POST request to the endpoint with the body.
+    fn post(path: &str, body: &str) -> String {
+        format!("POST {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", path, body.len(), body)
+    }
+
+    #[test]
+    fn test_methods() {
+        let response = request("OPTIONS /solve HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 204 No Content\r\n"));
+        assert!(response.contains("Access-Control-Allow-Origin: http://localhost:3000\r\n"));
+        let response = request("GET /solve HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+        assert!(response.ends_with(r#"{"error":"GET is not supported, use POST"}"#));
+    }
+
+    #[test]
+    fn test_content_length() {
+        let response = request("POST /solve HTTP/1.1\r\ncontent-length: many\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+        let response = request("POST /solve HTTP/1.1\r\nContent-Length: 2000000\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+        // Without a Content-Length, the body is empty.
+        let response = request("POST /solve HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let response = request(&post("/solve", BOARD));
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let output: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(output["version"], json::SCHEMA_VERSION);
+        assert!(!output["frontier"].as_array().unwrap().is_empty());
+        let response = request(&post("/analyze", BOARD));
+        assert!(response.ends_with(r#"{"error":"Missing budget"}"#));
+        let response = request(&post("/unknown", BOARD));
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn test_max_rewards() {
+        let challenge =
+            r#"{"name": "C", "target": 10, "abilities": 3, "reward": "+1 Calmness Strength"}"#;
+        let challenges = [challenge; MAX_REWARDS + 1].join(", ");
+        let body = format!(r#"{{"challenges": [{}]}}"#, challenges);
+        let response = request(&post("/solve", &body));
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+        assert!(response.contains("challenges with rewards"));
+    }
+}
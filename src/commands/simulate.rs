@@ -0,0 +1,68 @@
+use clap::Clap;
+use rand::{rngs::StdRng, SeedableRng};
+
+use btv::{explain::explain, simulator::simulate, solver};
+
+use super::BoardArgs;
+
+/// Arguments of the `simulate` command.
+#[derive(Clap)]
+pub struct SimulateArgs {
+    #[clap(flatten)]
+    board: BoardArgs,
+
+    /// The total cost of the abilities to simulate the best plan at.
+    #[clap(long)]
+    budget: u32,
+
+    /// The number of games to play, at least one.
+    #[clap(long, default_value = "100000")]
+    runs: u32,
+
+    /// The seed of the random number generator.
+    #[clap(long, default_value = "0")]
+    seed: u64,
+}
+
+pub fn run(args: SimulateArgs) {
+    let board = args.board.load();
+
+    eprintln!("Searching for optimal challenge order");
+    let frontier = board.solver.solve_from(&board.challenges, &board.state, &board.constraints);
+    let solution = match solver::best_within(&frontier, args.budget) {
+        Some(solution) => solution,
+        None => {
+            println!("No plan costs at most {} points", args.budget);
+            return;
+        }
+    };
+
+    eprintln!("Simulating {} games", args.runs);
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let simulation = simulate(&board.challenges, &board.state, solution, args.runs, &mut rng);
+    let (low, high) = simulation.confidence_interval(1.96);
+
+    println!("Cost: {}", solution.cost);
+    println!("Predicted success chance: {:.4}", solution.log_proba.exp());
+    println!(
+        "Simulated success chance: {:.4} (95% CI {:.4} - {:.4}, {} runs)",
+        simulation.pass_rate(),
+        low,
+        high,
+        simulation.runs
+    );
+    println!();
+    println!("{:<30} {:>9} {:>9} {:>9}", "Challenge", "Predicted", "Simulated", "Attempts");
+    let explanations = explain(&board.challenges, &board.state, solution);
+    for (step, explanation) in explanations.iter().enumerate() {
+        let attempts = simulation.attempts[step];
+        let simulated = match attempts {
+            0 => "-".to_string(),
+            _ => format!("{:.4}", simulation.challenge_passes[step] as f64 / attempts as f64),
+        };
+        println!(
+            "{:<30} {:>9.4} {:>9} {:>9}",
+            board.challenges[explanation.challenge].name, explanation.proba, simulated, attempts
+        );
+    }
+}
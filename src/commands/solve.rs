@@ -0,0 +1,396 @@
+use std::ops::Add;
+
+use clap::{ArgEnum, Clap};
+
+use btv::{
+    abilities::Abilities,
+    challenges::Challenge,
+    constraints::Constraint,
+    csv, json, parser,
+    plot::{self, Series},
+    solver::{self, Objective, SolutionVertex},
+    state::GameState,
+    Solver,
+};
+
+use super::{parse_top_k, parsed_or_exit, read_file_or_exit, watch, BoardArgs};
+
+/// Arguments of the `solve` command.
+#[derive(Clap)]
+pub struct SolveArgs {
+    #[clap(flatten)]
+    board: BoardArgs,
+
+    /// The number of distinct plans to print at each budget, at most 100.
+    #[clap(long, default_value = "1", parse(try_from_str = parse_top_k))]
+    top_k: usize,
+
+    /// Hide plans with a success chance below this value. Defaults to 0.1, or 0 with another
+    /// objective.
+    #[clap(long)]
+    hide_below: Option<f32>,
+
+    /// Hide plans that cost less than this value.
+    #[clap(long)]
+    min_cost: Option<u32>,
+
+    /// Hide plans that cost more than this value.
+    #[clap(long)]
+    max_cost: Option<u32>,
+
+    /// The format of the printed plans.
+    #[clap(arg_enum, long, default_value = "text")]
+    output: OutputFormat,
+
+    /// The quantity to maximize.
+    #[clap(arg_enum, long, default_value = "pass-all")]
+    objective: ObjectiveArg,
+
+    /// Only print the cheapest plan with at least this success chance, along with the points left.
+    #[clap(long)]
+    min_success: Option<f32>,
+
+    /// The points to buy abilities with. Defaults to the points of the state file, or an unlimited
+    /// budget.
+    #[clap(long)]
+    budget: Option<u32>,
+
+    /// Print a chart of the success chance against cost after the plans.
+    #[clap(arg_enum, long)]
+    chart: Option<ChartStyle>,
+
+    /// Print the budget beyond which every 10 extra points add less than this success chance.
+    /// Repeat to report several.
+    #[clap(long, number_of_values = 1)]
+    knee: Vec<f32>,
+
+    /// Keep polling the input file, re-solving it and printing how the best plan changed after
+    /// every edit. Only `--budget` and the board options apply to the recommended plan.
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "top-k", "hide-below", "min-cost", "max-cost", "output", "objective", "min-success",
+            "chart", "knee", "values",
+        ]
+    )]
+    watch: bool,
+
+    /// The path of a file with the value of passing each challenge, required by the expected value
+    /// objective. Unlisted challenges are worth one.
+    #[clap(long, required_if_eq("objective", "expected-value"))]
+    values: Option<String>,
+}
+
+/// The quantities the solver can maximize.
+#[derive(ArgEnum, Copy, Clone, Eq, PartialEq)]
+pub enum ObjectiveArg {
+    /// The probability of passing every challenge.
+    PassAll,
+    /// The expected number of challenges passed.
+    ExpectedPassed,
+    /// The expected value of the challenges passed, see `--values`.
+    ExpectedValue,
+}
+
+/// The characters a chart can be drawn with.
+#[derive(ArgEnum, Copy, Clone, Eq, PartialEq)]
+pub enum ChartStyle {
+    /// Unicode block elements.
+    Unicode,
+    /// ASCII characters only.
+    Ascii,
+}
+
+/// The size of the terminal chart, in columns and rows.
+const CHART_SIZE: (usize, usize) = (60, 10);
+
+/// The extra points over which knees are measured.
+const KNEE_STEP: u32 = 10;
+
+/// The default success chance below which plans are hidden.
+const HIDE_BELOW: f32 = 0.1;
+
+/// The formats the frontier can be printed in.
+#[derive(ArgEnum, Copy, Clone, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// Human readable text.
+    Text,
+    /// A JSON object, see the `json` module for the schema.
+    Json,
+    /// A CSV table with one row per plan, see the `csv` module for the columns.
+    Csv,
+    /// A CSV table with one row per plan and challenge, holding the dice, the active rewards and
+    /// the chance of passing.
+    CsvSetups,
+}
+
+pub fn run(args: SolveArgs) {
+    if args.watch {
+        watch::run(&args.board, &args.board.solver.build(), args.budget);
+        return;
+    }
+
+    let mut board = args.board.load();
+    if args.budget.is_some() {
+        board.state.points = args.budget;
+    }
+    let (challenges, state, constraints, solver) =
+        (&board.challenges, &board.state, &board.constraints, &board.solver);
+
+    let objective = match args.objective {
+        ObjectiveArg::PassAll => Objective::PassAll,
+        ObjectiveArg::ExpectedPassed => Objective::Expected(vec![1.0; challenges.len()]),
+        ObjectiveArg::ExpectedValue => {
+            // `--values` is required by the expected value objective.
+            let path = args.values.as_ref().unwrap();
+            eprintln!("Parsing challenge values");
+            let values = parser::parse_values(read_file_or_exit(path), challenges);
+            Objective::Expected(parsed_or_exit(path, values))
+        }
+    };
+
+    eprintln!("Searching for optimal challenge order");
+    if let Some(proba) = args.min_success {
+        // Search beyond the points left, so that a plan costing more is reported as unaffordable.
+        let unlimited = without_budget(state);
+        let frontier = solver.solve_objective(challenges, &unlimited, constraints, 1, &objective);
+        let best: Vec<SolutionVertex> =
+            frontier.into_iter().map(|mut plans| plans.swap_remove(0)).collect();
+        print_cheapest(challenges, state, &best, proba, args.output);
+        return;
+    }
+    let frontier = if objective != Objective::PassAll {
+        solver.solve_objective(challenges, state, constraints, args.top_k, &objective)
+    } else if args.top_k > 1 {
+        solver.solve_top_k(challenges, state, constraints, args.top_k)
+    } else {
+        let solutions = if *state != GameState::new() || !constraints.is_empty() {
+            solver.solve_from(challenges, state, constraints)
+        } else {
+            solver.solve(challenges)
+        };
+        solutions.into_iter().map(|solution| vec![solution]).collect()
+    };
+
+    let default_hide_below = if objective == Objective::PassAll { HIDE_BELOW } else { 0.0 };
+    let hide_below = args.hide_below.unwrap_or(default_hide_below);
+    let min_cost = args.min_cost.unwrap_or(0);
+    let max_cost = args.max_cost.unwrap_or(u32::MAX);
+    let frontier: Vec<Vec<SolutionVertex>> = frontier
+        .into_iter()
+        .filter(|plans| plans[0].log_proba.exp() >= hide_below)
+        .filter(|plans| (min_cost..=max_cost).contains(&plans[0].cost))
+        .collect();
+
+    if args.output == OutputFormat::Json {
+        let mut output = json::Frontier::new(challenges, &frontier);
+        if objective != Objective::PassAll {
+            output = output.with_expected_values(&frontier);
+        }
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        return;
+    }
+    if args.output == OutputFormat::Csv || args.output == OutputFormat::CsvSetups {
+        let best: Vec<SolutionVertex> = frontier.iter().map(|plans| plans[0].clone()).collect();
+        print_csv(challenges, state, &best, args.output);
+        return;
+    }
+
+    let expected = objective != Objective::PassAll;
+    for plans in &frontier {
+        if args.top_k == 1 {
+            print_solution(&plans[0], state, expected);
+            continue;
+        }
+        println!("Budget: {}", plans[0].cost);
+        for (idx, plan) in plans.iter().enumerate() {
+            println!("Plan {}:", idx + 1);
+            print_solution(plan, state, expected);
+        }
+    }
+
+    let best_plans: Vec<SolutionVertex> = frontier.iter().map(|plans| plans[0].clone()).collect();
+    if let Some(style) = args.chart {
+        let series = Series::new("", &best_plans);
+        let (width, height) = CHART_SIZE;
+        print!("{}", plot::terminal(&series, width, height, style == ChartStyle::Ascii));
+        println!();
+    }
+    for gain in &args.knee {
+        if let Some(budget) = solver::knee(&best_plans, KNEE_STEP, *gain) {
+            let proba = solver::best_within(&best_plans, budget).map_or(0.0, |v| v.log_proba.exp());
+            println!(
+                "Knee: beyond {} points, {} more points add less than {} (success chance {})",
+                budget, KNEE_STEP, gain, proba
+            );
+        }
+    }
+
+    if !constraints.is_empty() {
+        print_constraint_costs(challenges, solver, state, constraints);
+    }
+}
+
+/// Prints the cost, success chance, order and abilities of a plan, along with the expected value
+/// if another objective was maximized.
+fn print_solution(solution: &SolutionVertex, state: &GameState, expected: bool) {
+    println!("Cost: {}", solution.cost);
+    println!("Success chance: {}", solution.log_proba.exp());
+    if expected {
+        println!("Expected value: {}", solution.value);
+    }
+    println!("Order: {:?}", solution.order);
+    let totals = solution.abilities.iter().fold(Abilities::new(), Abilities::add);
+    println!("Totals: {:?}", totals);
+    if state.points.is_some() {
+        let to_buy = state.to_buy(&totals);
+        println!("To buy: {:?} ({} points)", to_buy, to_buy.cost());
+    }
+    println!("Abilities:");
+    for abilities in solution.abilities.iter() {
+        println!("{:?}", abilities);
+    }
+    println!();
+}
+
+/// Returns the game state with an unlimited budget.
+fn without_budget(state: &GameState) -> GameState {
+    GameState {
+        completed: state.completed.clone(),
+        dice: state.dice,
+        points: None,
+    }
+}
+
+/// Prints the cheapest plan reaching a success chance, along with the points left for the finale.
+fn print_cheapest(
+    challenges: &[Challenge],
+    state: &GameState,
+    frontier: &[SolutionVertex],
+    proba: f32,
+    output: OutputFormat,
+) {
+    let solution = solver::cheapest_reaching(frontier, proba);
+    let totals = solution
+        .map(|solution| solution.abilities.iter().fold(Abilities::new(), Abilities::add));
+    let points_left = totals.and_then(|totals| state.points_left(&totals));
+    let affordable = totals.is_none_or(|totals| state.can_afford(&totals));
+
+    if output == OutputFormat::Json {
+        let plan = solution.map(|solution| json::Plan::new(challenges, solution));
+        let output = serde_json::json!({
+            "plan": plan,
+            "points_left": points_left,
+            "affordable": affordable,
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        return;
+    }
+    if output == OutputFormat::Csv || output == OutputFormat::CsvSetups {
+        print_csv(challenges, state, &solution.into_iter().cloned().collect::<Vec<_>>(), output);
+        return;
+    }
+
+    match solution {
+        Some(solution) => {
+            print_solution(solution, state, false);
+            match points_left {
+                Some(points) => println!("Points left for the finale: {}", points),
+                None if !affordable => println!("Points left for the finale: none, unaffordable"),
+                None => println!("Points left for the finale: unknown, pass --budget"),
+            }
+        }
+        None => {
+            let best = frontier.last().map_or(0.0, |v| v.log_proba.exp());
+            println!("No plan reaches a success chance of {}, the best is {}", proba, best);
+        }
+    }
+}
+
+/// Prints the plans as one of the CSV tables.
+fn print_csv(
+    challenges: &[Challenge],
+    state: &GameState,
+    frontier: &[SolutionVertex],
+    output: OutputFormat,
+) {
+    if output == OutputFormat::CsvSetups {
+        print!("{}", csv::setups(challenges, state, frontier));
+    } else {
+        print!("{}", csv::frontier(challenges, frontier));
+    }
+}
+
+/// Prints what each constraint costs against the unconstrained optimum.
+///
+/// With a budget, the success chances within it are compared, along with the extra points the
+/// constraint needs to reach the unconstrained chance. Without one, the extra points are averaged
+/// over the plans of the unconstrained frontier.
+fn print_constraint_costs(
+    challenges: &[Challenge],
+    solver: &Solver,
+    state: &GameState,
+    constraints: &[Constraint],
+) {
+    // Solve beyond the budget, to find the extra points that the constraints need.
+    let unlimited = without_budget(state);
+    let unconstrained = solver.solve_from(challenges, &unlimited, &[]);
+    let mut costs: Vec<(String, Vec<SolutionVertex>)> = constraints
+        .iter()
+        .map(|constraint| {
+            let frontier = solver.solve_from(challenges, &unlimited, &[*constraint]);
+            (constraint.describe(challenges), frontier)
+        })
+        .collect();
+    let frontier = solver.solve_from(challenges, &unlimited, constraints);
+    costs.push(("All constraints".to_string(), frontier));
+    // The extra points a constrained frontier needs to match an unconstrained plan.
+    let extra = |frontier: &[SolutionVertex], plan: &SolutionVertex| {
+        let proba = plan.log_proba.exp();
+        solver::cheapest_reaching(frontier, proba).map(|v| v.cost as i64 - plan.cost as i64)
+    };
+
+    match state.points {
+        Some(budget) => {
+            let plan = match solver::best_within(&unconstrained, budget) {
+                Some(plan) => plan,
+                None => {
+                    println!("Unconstrained success chance at {} points: infeasible", budget);
+                    return;
+                }
+            };
+            let unconstrained_proba = plan.log_proba.exp();
+            println!("Unconstrained success chance at {} points: {}", budget, unconstrained_proba);
+            println!("Constraint costs at {} points:", budget);
+            for (name, frontier) in costs {
+                let proba = match solver::best_within(&frontier, budget) {
+                    Some(vertex) => vertex.log_proba.exp(),
+                    None => {
+                        println!("{}: infeasible", name);
+                        continue;
+                    }
+                };
+                let points = match extra(&frontier, plan) {
+                    Some(points) => format!("{:+} points", points),
+                    None => "unreachable".to_string(),
+                };
+                let delta = proba - unconstrained_proba;
+                println!("{}: {} ({:+}), same chance at {}", name, proba, delta, points);
+            }
+        }
+        None => {
+            println!("Constraint costs, in extra points for the same success chance:");
+            for (name, frontier) in costs {
+                if frontier.is_empty() {
+                    println!("{}: infeasible", name);
+                    continue;
+                }
+                let extras: Vec<i64> =
+                    unconstrained.iter().filter_map(|plan| extra(&frontier, plan)).collect();
+                let mean = extras.iter().sum::<i64>() as f32 / extras.len().max(1) as f32;
+                let best = frontier.last().unwrap().log_proba.exp();
+                println!("{}: {:+.1} points on average, best success chance {}", name, mean, best);
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::io::{self, BufRead, Write};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use btv::{
+    json::{self, BoardState},
+    parser,
+    solver::{self, SolutionVertex},
+    Challenge, GameState, Solver,
+};
+
+use super::check_top_k;
+
+/// An operation on the board held in memory. Any `id` field of a request is echoed in the
+/// response.
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Operation {
+    /// Replaces the board with one in the text format, and resets the game state.
+    Parse { text: String },
+
+    /// Replaces the board and game state with a JSON board.
+    Board { board: json::Board },
+
+    /// Solves the board from the game state.
+    Solve { top_k: Option<usize> },
+
+    /// Returns the best plan within a budget.
+    Query { budget: u32 },
+
+    /// Replaces the game state.
+    Update { state: BoardState },
+}
+
+/// The board held in memory between requests.
+#[derive(Default)]
+struct Session {
+    challenges: Vec<Challenge>,
+    state: GameState,
+
+    /// The frontier of the board and state, if it was solved since they last changed.
+    frontier: Option<Vec<Vec<SolutionVertex>>>,
+}
+
+/// Answers line-delimited JSON requests from stdin, writing one JSON response per line to stdout.
+pub fn run(solver: Solver) {
+    eprintln!("Reading requests from stdin");
+    let mut session = Session::default();
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for line in stdin.lock().split(b'\n') {
+        // Stdin cannot be read past an error, so report it and stop.
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                writeln!(stdout, "{}", json!({ "error": err.to_string() })).unwrap();
+                stdout.flush().unwrap();
+                return;
+            }
+        };
+        let line = match String::from_utf8(line) {
+            Ok(line) => line,
+            Err(err) => {
+                writeln!(stdout, "{}", json!({ "error": err.to_string() })).unwrap();
+                stdout.flush().unwrap();
+                continue;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut response = match serde_json::from_str::<Operation>(&line) {
+            Ok(operation) => match session.apply(&solver, operation) {
+                Ok(body) => body,
+                Err(error) => json!({ "error": error }),
+            },
+            Err(err) => json!({ "error": err.to_string() }),
+        };
+        // Tag the response with the id of the request.
+        let id = serde_json::from_str::<Value>(&line).ok().and_then(|v| v.get("id").cloned());
+        if let (Some(id), Some(response)) = (id, response.as_object_mut()) {
+            response.insert("id".to_string(), id);
+        }
+        writeln!(stdout, "{}", response).unwrap();
+        stdout.flush().unwrap();
+    }
+}
+
+impl Session {
+    /// Applies an operation, returning the response body or an error message.
+    fn apply(&mut self, solver: &Solver, operation: Operation) -> Result<Value, String> {
+        match operation {
+            Operation::Parse { text } => {
+                let challenges = parser::parse(text)?;
+                self.replace(challenges, GameState::new());
+                Ok(json!({ "board": json::Board::new(&self.challenges, &self.state) }))
+            }
+            Operation::Board { board } => {
+                let (challenges, state) = board.parse().ok_or("Invalid board")?;
+                self.replace(challenges, state);
+                Ok(json!({ "board": json::Board::new(&self.challenges, &self.state) }))
+            }
+            Operation::Solve { top_k } => {
+                let top_k = check_top_k(top_k.unwrap_or(1))?;
+                let frontier = solver.solve_top_k(&self.challenges, &self.state, &[], top_k);
+                let output = json::Frontier::new(&self.challenges, &frontier);
+                self.frontier = Some(frontier);
+                Ok(json!(output))
+            }
+            Operation::Query { budget } => {
+                let challenges = &self.challenges;
+                let state = &self.state;
+                let frontier = self
+                    .frontier
+                    .get_or_insert_with(|| solver.solve_top_k(challenges, state, &[], 1));
+                let best: Vec<SolutionVertex> = frontier.iter().map(|p| p[0].clone()).collect();
+                let plan = solver::best_within(&best, budget);
+                Ok(json!({ "plan": plan.map(|plan| json::Plan::new(challenges, plan)) }))
+            }
+            Operation::Update { state } => {
+                let board = json::Board {
+                    state: Some(state),
+                    ..json::Board::new(&self.challenges, &GameState::new())
+                };
+                let (challenges, state) = board.parse().ok_or("Unknown completed challenge")?;
+                self.replace(challenges, state);
+                Ok(json!({ "board": json::Board::new(&self.challenges, &self.state) }))
+            }
+        }
+    }
+
+    /// Replaces the board and game state, discarding the frontier.
+    fn replace(&mut self, challenges: Vec<Challenge>, state: GameState) {
+        self.challenges = challenges;
+        self.state = state;
+        self.frontier = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::*, *};
+
+    /// Applies a request to the session, returning the response body or an error message.
+    fn apply(session: &mut Session, request: &str) -> Result<Value, String> {
+        let operation = serde_json::from_str(request).map_err(|err| err.to_string())?;
+        session.apply(solver(), operation)
+    }
+
+    #[test]
+    fn test_board() {
+        let mut session = Session::default();
+        let response = apply(&mut session, &format!(r#"{{"op": "board", "board": {}}}"#, BOARD));
+        let response = response.unwrap();
+        assert_eq!(response["board"]["challenges"][0]["name"], "Monologue");
+        assert_eq!(session.challenges, board().0);
+        assert!(session.frontier.is_none());
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut session = Session::default();
+        let text = "Quickteams\tTarget: 10\tMax Abilities: 3\nReward: +1 Diction Range\n";
+        let request = json!({ "op": "parse", "text": text });
+        let response = apply(&mut session, &request.to_string()).unwrap();
+        assert_eq!(response["board"]["challenges"][0]["target"], 10);
+        let response = apply(&mut session, r#"{"op": "parse", "text": "Nope"}"#);
+        assert!(response.unwrap_err().starts_with("Invalid line 1"));
+    }
+
+    #[test]
+    fn test_update_and_query() {
+        let mut session = Session::default();
+        apply(&mut session, &format!(r#"{{"op": "board", "board": {}}}"#, BOARD)).unwrap();
+        let response = apply(&mut session, r#"{"op": "query", "budget": 60}"#).unwrap();
+        assert_eq!(response["plan"]["order"].as_array().unwrap().len(), 2);
+        assert!(session.frontier.is_some());
+        let response = apply(&mut session, r#"{"op": "query", "budget": 0}"#).unwrap();
+        assert!(response["plan"].is_null());
+
+        // Updating the state discards the frontier and only plans the remaining challenges.
+        let update = r#"{"op": "update", "state": {"completed": ["Monologue"], "points": 60}}"#;
+        let response = apply(&mut session, update).unwrap();
+        assert_eq!(response["board"]["state"]["completed"][0], "Monologue");
+        assert!(session.frontier.is_none());
+        let response = apply(&mut session, r#"{"op": "query", "budget": 60}"#).unwrap();
+        assert_eq!(response["plan"]["order"], json!(["Quickteams"]));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut session = Session::default();
+        let response = apply(&mut session, r#"{"op": "board", "board": {"challenges": [
+            {"name": "A", "target": 12, "abilities": 3, "reward": "None"}
+        ]}}"#);
+        assert_eq!(response.unwrap_err(), "Invalid board");
+        let response = apply(&mut session, r#"{"op": "update", "state": {"completed": ["B"]}}"#);
+        assert_eq!(response.unwrap_err(), "Unknown completed challenge");
+        let response = apply(&mut session, r#"{"op": "solve", "top_k": 0}"#);
+        assert!(response.is_err());
+        assert!(apply(&mut session, r#"{"op": "unknown"}"#).is_err());
+    }
+}
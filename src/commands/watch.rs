@@ -0,0 +1,209 @@
+use std::{fmt::Write, fs, ops::Add, thread, time::Duration};
+
+use btv::{abilities::Abilities, challenges::Challenge, solver::SolutionVertex, Solver};
+
+use super::BoardArgs;
+
+/// The time between two reads of the board file.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A recommended plan, with the challenges referred to by name.
+struct Plan {
+    cost: u32,
+    proba: f32,
+    order: Vec<String>,
+    abilities: Vec<Abilities>,
+}
+
+/// Polls the board file, re-solving it on every change and printing how the best plan changed.
+///
+/// The challenge setups of the solver only depend on its config, so they are kept between runs.
+/// With a `budget`, the best plan within it is recommended.
+pub fn run(args: &BoardArgs, solver: &Solver, budget: Option<u32>) {
+    if args.input == "-" {
+        eprintln!("Cannot watch stdin, pass the path of the board file");
+        return;
+    }
+    let mut contents = None;
+    let mut previous: Option<Plan> = None;
+    eprintln!("Watching {} for changes", args.input);
+    loop {
+        let current = fs::read_to_string(&args.input).ok();
+        if current.is_some() && current != contents {
+            contents = current;
+            // Parse the text that was compared, in case the file changed since.
+            match args.parse_text(contents.as_deref().unwrap()) {
+                Err(message) => eprintln!("{}, waiting for the next change", message),
+                Ok((challenges, _, _)) if has_duplicate_names(&challenges) => {
+                    eprintln!("Challenges are matched by name, rename the duplicates to continue")
+                }
+                Ok((challenges, mut state, constraints)) => {
+                    if budget.is_some() {
+                        state.points = budget;
+                    }
+                    eprintln!("Searching for optimal challenge order");
+                    let frontier = solver.solve_from(&challenges, &state, &constraints);
+                    let plan = frontier.last().map(|solution| Plan::new(&challenges, solution));
+                    println!();
+                    print!("{}", diff(previous.as_ref(), plan.as_ref()));
+                    previous = plan;
+                }
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Returns whether two challenges share a name, so that plans cannot be compared by name.
+fn has_duplicate_names(challenges: &[Challenge]) -> bool {
+    challenges.iter().enumerate().any(|(idx, challenge)| {
+        challenges[..idx].iter().any(|other| other.name == challenge.name)
+    })
+}
+
+impl Plan {
+    fn new(challenges: &[Challenge], solution: &SolutionVertex) -> Plan {
+        Plan {
+            cost: solution.cost,
+            proba: solution.log_proba.exp(),
+            order: solution.order.iter().map(|idx| challenges[*idx].name.clone()).collect(),
+            abilities: solution.abilities.clone(),
+        }
+    }
+
+    /// Returns the dice used on a challenge, if the plan attempts it.
+    fn dice(&self, name: &str) -> Option<Abilities> {
+        self.order.iter().position(|n| n == name).map(|idx| self.abilities[idx])
+    }
+}
+
+/// Returns the new plan, next to the previous one where they differ.
+fn diff(previous: Option<&Plan>, plan: Option<&Plan>) -> String {
+    let mut output = String::new();
+    let (previous, plan) = match (previous, plan) {
+        (Some(previous), Some(plan)) => (previous, plan),
+        (_, None) => {
+            writeln!(output, "No plan can pass every challenge").unwrap();
+            return output;
+        }
+        (None, Some(plan)) => {
+            writeln!(output, "Cost: {}", plan.cost).unwrap();
+            writeln!(output, "Success chance: {:.4}", plan.proba).unwrap();
+            writeln!(output, "Order: {}", plan.order.join(", ")).unwrap();
+            for (name, abilities) in plan.order.iter().zip(&plan.abilities) {
+                writeln!(output, "  {}: {}", name, abilities).unwrap();
+            }
+            return output;
+        }
+    };
+
+    writeln!(output, "Cost: {} -> {}", previous.cost, plan.cost).unwrap();
+    writeln!(
+        output,
+        "Success chance: {:.4} -> {:.4} ({:+.4})",
+        previous.proba,
+        plan.proba,
+        plan.proba - previous.proba
+    )
+    .unwrap();
+    if previous.order == plan.order {
+        writeln!(output, "Order: unchanged").unwrap();
+    } else {
+        writeln!(output, "Order: {}", plan.order.join(", ")).unwrap();
+    }
+    let mut changed = false;
+    for name in &plan.order {
+        let dice = plan.dice(name).unwrap();
+        match previous.dice(name) {
+            Some(old) if old == dice => continue,
+            Some(old) => writeln!(output, "  {}: {} -> {}", name, old, dice).unwrap(),
+            None => writeln!(output, "  + {}: {}", name, dice).unwrap(),
+        }
+        changed = true;
+    }
+    for name in previous.order.iter().filter(|name| plan.dice(name).is_none()) {
+        writeln!(output, "  - {}", name).unwrap();
+        changed = true;
+    }
+    if !changed {
+        writeln!(output, "Dice: unchanged").unwrap();
+    }
+    let totals = |plan: &Plan| plan.abilities.iter().fold(Abilities::new(), Abilities::add);
+    if totals(previous) != totals(plan) {
+        writeln!(output, "Totals: {} -> {}", totals(previous), totals(plan)).unwrap();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use btv::abilities::Ability;
+
+    use super::{super::tests::*, *};
+
+    fn dice(ability: Ability, count: u8) -> Abilities {
+        let mut abilities = Abilities::new();
+        abilities[ability] = count;
+        abilities
+    }
+
+    fn plan(cost: u32, proba: f32, dice: &[(&str, Abilities)]) -> Plan {
+        Plan {
+            cost,
+            proba,
+            order: dice.iter().map(|(name, _)| name.to_string()).collect(),
+            abilities: dice.iter().map(|(_, abilities)| *abilities).collect(),
+        }
+    }
+
+    #[test]
+    fn test_has_duplicate_names() {
+        let (mut challenges, _) = board();
+        assert!(!has_duplicate_names(&challenges));
+        challenges.push(challenges[0].clone());
+        assert!(has_duplicate_names(&challenges));
+        assert!(!has_duplicate_names(&[]));
+    }
+
+    #[test]
+    fn test_diff_first_and_none() {
+        let first = plan(12, 0.5, &[("Quickteams", dice(Ability::Diction, 2))]);
+        assert_eq!(
+            diff(None, Some(&first)),
+            "Cost: 12\nSuccess chance: 0.5000\nOrder: Quickteams\n  Quickteams: 2 Diction\n"
+        );
+        assert_eq!(diff(Some(&first), None), "No plan can pass every challenge\n");
+        assert_eq!(diff(None, None), "No plan can pass every challenge\n");
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let first = plan(12, 0.5, &[("Quickteams", dice(Ability::Diction, 2))]);
+        assert_eq!(
+            diff(Some(&first), Some(&first)),
+            "Cost: 12 -> 12\nSuccess chance: 0.5000 -> 0.5000 (+0.0000)\nOrder: unchanged\n\
+             Dice: unchanged\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_changed() {
+        let previous = plan(
+            12,
+            0.5,
+            &[("Quickteams", dice(Ability::Diction, 2)), ("Monologue", Abilities::new())],
+        );
+        let plan = plan(
+            20,
+            0.25,
+            &[("Encore", dice(Ability::Atmosphere, 2)), ("Quickteams", dice(Ability::Diction, 1))],
+        );
+        assert_eq!(
+            diff(Some(&previous), Some(&plan)),
+            "Cost: 12 -> 20\nSuccess chance: 0.5000 -> 0.2500 (-0.2500)\n\
+             Order: Encore, Quickteams\n  + Encore: 2 Atmosphere\n\
+             \x20 Quickteams: 2 Diction -> 1 Diction\n  - Monologue\n\
+             Totals: 2 Diction -> 2 Atmosphere, 1 Diction\n"
+        );
+    }
+}
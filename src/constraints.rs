@@ -0,0 +1,108 @@
+use crate::challenges::Challenge;
+
+/// A constraint on the order in which the challenges are attempted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Constraint {
+    /// The challenge must be attempted at the given position, counting from zero.
+    Pin { challenge: usize, position: usize },
+
+    /// The first challenge must be attempted before the second challenge.
+    Before { first: usize, second: usize },
+
+    /// The challenge is left out of the plan.
+    Exclude { challenge: usize },
+}
+
+impl Constraint {
+    /// Returns whether the challenge order satisfies the constraint.
+    pub fn allows(&self, order: &[usize]) -> bool {
+        match *self {
+            Constraint::Pin {
+                challenge,
+                position,
+            } => order.get(position) == Some(&challenge),
+            Constraint::Before { first, second } => {
+                let first = order.iter().position(|idx| *idx == first);
+                let second = order.iter().position(|idx| *idx == second);
+                match (first, second) {
+                    (Some(first), Some(second)) => first < second,
+                    _ => true,
+                }
+            }
+            Constraint::Exclude { challenge } => !order.contains(&challenge),
+        }
+    }
+
+    /// Returns whether the constraint restricts the position of the challenge.
+    pub fn orders(&self, challenge_idx: usize) -> bool {
+        match *self {
+            Constraint::Pin { challenge, .. } => challenge == challenge_idx,
+            Constraint::Before { first, second } => {
+                first == challenge_idx || second == challenge_idx
+            }
+            Constraint::Exclude { .. } => false,
+        }
+    }
+
+    /// Returns the constraint over the remaining challenges once the first challenge of the order
+    /// has been completed, or `None` if it no longer applies.
+    pub fn after_completing(&self, challenge_idx: usize) -> Option<Constraint> {
+        match *self {
+            Constraint::Pin { challenge, .. } if challenge == challenge_idx => None,
+            Constraint::Pin {
+                challenge,
+                position,
+            } => Some(Constraint::Pin {
+                challenge,
+                position: position.saturating_sub(1),
+            }),
+            Constraint::Before { first, second }
+                if first == challenge_idx || second == challenge_idx =>
+            {
+                None
+            }
+            _ => Some(*self),
+        }
+    }
+
+    /// Returns a human readable description of the constraint.
+    pub fn describe(&self, challenges: &[Challenge]) -> String {
+        match *self {
+            Constraint::Pin {
+                challenge,
+                position,
+            } => format!("{} at position {}", challenges[challenge].name, position + 1),
+            Constraint::Before { first, second } => format!(
+                "{} before {}",
+                challenges[first].name, challenges[second].name
+            ),
+            Constraint::Exclude { challenge } => format!("Skip {}", challenges[challenge].name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows() {
+        let order = [2, 0, 1];
+        assert!(Constraint::Pin { challenge: 2, position: 0 }.allows(&order));
+        assert!(!Constraint::Pin { challenge: 2, position: 1 }.allows(&order));
+        assert!(Constraint::Before { first: 0, second: 1 }.allows(&order));
+        assert!(!Constraint::Before { first: 1, second: 2 }.allows(&order));
+        assert!(Constraint::Exclude { challenge: 3 }.allows(&order));
+        assert!(!Constraint::Exclude { challenge: 0 }.allows(&order));
+    }
+
+    #[test]
+    fn test_after_completing() {
+        let pin = Constraint::Pin { challenge: 2, position: 1 };
+        assert_eq!(pin.after_completing(0), Some(Constraint::Pin { challenge: 2, position: 0 }));
+        assert_eq!(pin.after_completing(2), None);
+        assert_eq!(Constraint::Before { first: 0, second: 1 }.after_completing(0), None);
+        let exclude = Constraint::Exclude { challenge: 3 };
+        assert_eq!(exclude.after_completing(0), Some(exclude));
+    }
+}
@@ -0,0 +1,131 @@
+//! CSV output of the solver.
+//!
+//! The frontier is written as one row per plan:
+//!
+//! ```text
+//! cost,probability,order
+//! 800,0.3303,Paperwork Montage; Creating Quickteams
+//! ```
+//!
+//! The setups are written in long form, as one row per plan and challenge in play order:
+//!
+//! ```text
+//! cost,position,challenge,Atmosphere,Diction,...,rewards,probability
+//! 800,1,Paperwork Montage,0,0,...,,0.5712
+//! ```
+//!
+//! with one column per ability holding the dice used on the challenge. `rewards` lists the rewards
+//! active while attempting the challenge, separated by semicolons and empty if there are none, and
+//! `probability` is the chance of passing it. Fields are quoted when they contain a comma, a quote
+//! or a line break.
+
+use crate::{
+    abilities::Ability, challenges::Challenge, explain::explain, solver::SolutionVertex,
+    state::GameState,
+};
+
+/// Returns the frontier as CSV, with one row per plan.
+pub fn frontier(challenges: &[Challenge], frontier: &[SolutionVertex]) -> String {
+    let mut output = String::from("cost,probability,order\n");
+    for solution in frontier {
+        let order: Vec<&str> =
+            solution.order.iter().map(|idx| challenges[*idx].name.as_str()).collect();
+        let row = [
+            solution.cost.to_string(),
+            solution.log_proba.exp().to_string(),
+            order.join("; "),
+        ];
+        push_row(&mut output, &row);
+    }
+    output
+}
+
+/// Returns the setups of the frontier as CSV, with one row per plan and challenge.
+pub fn setups(challenges: &[Challenge], state: &GameState, frontier: &[SolutionVertex]) -> String {
+    let mut header = vec!["cost".to_string(), "position".to_string(), "challenge".to_string()];
+    header.extend(Ability::values().iter().map(|ability| ability.name().to_string()));
+    header.push("rewards".to_string());
+    header.push("probability".to_string());
+    let mut output = String::new();
+    push_row(&mut output, &header);
+
+    for solution in frontier {
+        for (position, explanation) in explain(challenges, state, solution).iter().enumerate() {
+            let mut row = vec![
+                solution.cost.to_string(),
+                (position + 1).to_string(),
+                challenges[explanation.challenge].name.clone(),
+            ];
+            let abilities = explanation.abilities;
+            row.extend(Ability::values().iter().map(|ability| abilities[*ability].to_string()));
+            row.push(explanation.rewards.descriptions().join("; "));
+            row.push(explanation.proba.to_string());
+            push_row(&mut output, &row);
+        }
+    }
+    output
+}
+
+/// Appends a row of fields, quoting them where needed.
+fn push_row<T: AsRef<str>>(output: &mut String, row: &[T]) {
+    for (idx, field) in row.iter().enumerate() {
+        if idx > 0 {
+            output.push(',');
+        }
+        let field = field.as_ref();
+        if field.contains(&[',', '"', '\n', '\r'][..]) {
+            output.push('"');
+            output.push_str(&field.replace('"', "\"\""));
+            output.push('"');
+        } else {
+            output.push_str(field);
+        }
+    }
+    output.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{abilities::Abilities, rewards::Rewards};
+
+    use super::*;
+
+    #[test]
+    fn test_csv() {
+        let challenge = |name: &str, reward| Challenge {
+            name: name.to_string(),
+            target_idx: 0,
+            abilities: 4,
+            reward,
+        };
+        let challenges = vec![
+            challenge("First, again", Rewards::NONE),
+            challenge("Second", Rewards::DICTION_STRENGTH),
+        ];
+        let mut diction = Abilities::new();
+        diction[Ability::Diction] = 2;
+        let solution = SolutionVertex {
+            cost: 60,
+            log_proba: 0.0,
+            value: 0.0,
+            order: vec![1, 0],
+            abilities: vec![diction, Abilities::new()],
+        };
+
+        assert_eq!(
+            frontier(&challenges, std::slice::from_ref(&solution)),
+            "cost,probability,order\n60,1,\"Second; First, again\"\n"
+        );
+
+        let output = setups(&challenges, &GameState::new(), &[solution]);
+        let rows: Vec<&str> = output.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].starts_with("cost,position,challenge,"));
+        assert!(rows[0].ends_with(",rewards,probability"));
+        let fields: Vec<&str> = rows[1].split(',').collect();
+        assert_eq!(fields[..5], ["60", "1", "Second", "0", "2"]);
+        assert_eq!(fields[11], "");
+        assert!(rows[2].starts_with("60,2,\"First, again\",0,0,"));
+        assert!(rows[2].contains(&format!(",{},", Rewards::DICTION_STRENGTH.descriptions()[0])));
+    }
+}
@@ -1,23 +1,51 @@
-use crate::abilities::Ability;
+use crate::abilities::{Abilities, Ability};
 use crate::rewards::Rewards;
 
-/// The number of distribution values to track. The target specified is at most 70.
-const TARGETS: usize = 71;
+/// The default number of distribution values to track. The target specified is at most 70.
+pub const DEFAULT_PRECISION: usize = 71;
 
 /// The distribution the sum of multiple ability rolls.
 #[derive(Clone, Debug)]
 pub struct Distribution {
     // The complementary cumulative distribution. `ccdf[target]` is the probability that
     // the outcome of the rolls is less than or equal to `target`.
-    ccdf: [f32; TARGETS],
+    ccdf: Vec<f32>,
+
+    // The expected outcome of the rolls. This is tracked separately, since outcomes above the
+    // largest target are not represented by `ccdf`.
+    mean: f32,
+}
+
+impl Default for Distribution {
+    fn default() -> Distribution {
+        Distribution::new()
+    }
 }
 
 impl Distribution {
     /// Returns a new distribution with no ability rolls.
     pub fn new() -> Distribution {
-        let mut ccdf = [0.0; TARGETS];
+        Distribution::with_precision(DEFAULT_PRECISION)
+    }
+
+    /// Returns a new distribution with no ability rolls, tracking outcomes below `precision`.
+    ///
+    /// The precision must be larger than any target passed to `at_least`.
+    pub fn with_precision(precision: usize) -> Distribution {
+        let mut ccdf = vec![0.0; precision];
         ccdf[0] = 1.0;
-        Distribution { ccdf }
+        Distribution { ccdf, mean: 0.0 }
+    }
+
+    /// Returns the distribution of rolling all the given abilities.
+    pub fn of(abilities: &Abilities, rewards: Rewards) -> Distribution {
+        let mut output = Distribution::new();
+        for ability in Ability::values() {
+            for _ in 0..abilities[ability] {
+                output = output.add_ability(ability, rewards);
+            }
+        }
+        output
     }
 
     /// Returns the probability that outcome is at least `target`.
@@ -25,42 +53,67 @@ impl Distribution {
         self.ccdf[target]
     }
 
+    /// Returns the expected outcome of the rolls.
+    pub fn mean(&self) -> f32 {
+        self.mean
+    }
+
     /// Returns a distribution equal to the sum of this distribution and the given ability roll.
     pub fn add_ability(&self, ability: Ability, rewards: Rewards) -> Distribution {
+        let mut output = self.clone();
+        self.add_ability_to(ability, rewards, &mut output);
+        output
+    }
+
+    /// Writes to `output` the sum of this distribution and the given ability roll.
+    ///
+    /// Reusing `output` avoids allocating a distribution per ability roll while computing the
+    /// search tables. `output` must have the same precision.
+    pub fn add_ability_to(&self, ability: Ability, rewards: Rewards, output: &mut Distribution) {
         match ability {
-            Ability::Atmosphere => self.add_die(
+            Ability::Atmosphere => self.add_die_to(
                 ability.cost() + (rewards.contains(Rewards::ATMOSPHERE_RANGE) as u32),
                 false,
+                output,
             ),
-            Ability::Diction => self.add_die(
+            Ability::Diction => self.add_die_to(
                 ability.cost() + (rewards.contains(Rewards::DICTION_RANGE) as u32),
                 rewards.contains(Rewards::DICTION_STRENGTH),
+                output,
             ),
-            Ability::Precision => self.add_die(
+            Ability::Precision => self.add_die_to(
                 ability.cost() + (rewards.contains(Rewards::PRECISION_RANGE) as u32),
                 rewards.contains(Rewards::PRECISION_STRENGTH),
+                output,
+            ),
+            Ability::Calmness => self.add_die_to(
+                ability.cost(),
+                rewards.contains(Rewards::CALMNESS_STRENGTH),
+                output,
             ),
-            Ability::Calmness => {
-                self.add_die(ability.cost(), rewards.contains(Rewards::CALMNESS_STRENGTH))
-            }
             Ability::Style => {
                 if rewards.contains(Rewards::STYLE_EXPLODING) {
-                    self.add_exploding_style()
+                    self.add_exploding_style_to(output)
                 } else {
-                    self.add_die(ability.cost(), false)
+                    self.add_die_to(ability.cost(), false, output)
                 }
             }
-            _ => self.add_die(ability.cost(), false),
+            _ => self.add_die_to(ability.cost(), false, output),
         }
     }
 
-    fn add_die(&self, range: u32, strength: bool) -> Distribution {
-        let mut output = Distribution::new();
+    fn add_die_to(&self, range: u32, strength: bool, output: &mut Distribution) {
         let mut window = range as f32;
         let range_us = range as usize;
 
+        // A strength die adds one to the roll, up to the range of the die.
+        output.mean = self.mean + (range as f32 + 1.0) / 2.0;
+        if strength {
+            output.mean += 1.0 - 1.0 / (range as f32);
+        }
+
         if strength {
-            for i in 1..TARGETS {
+            for i in 1..self.ccdf.len() {
                 output.ccdf[i] = window / (range as f32);
                 window += self.ccdf[i - 1];
                 window += if i + 1 >= range_us {
@@ -76,7 +129,7 @@ impl Distribution {
                     };
             }
         } else {
-            for i in 1..TARGETS {
+            for i in 1..self.ccdf.len() {
                 output.ccdf[i] = window / (range as f32);
                 window += self.ccdf[i];
                 window -= if i >= range_us {
@@ -86,27 +139,27 @@ impl Distribution {
                 };
             }
         }
-
-        output
     }
 
-    fn add_exploding_style(&self) -> Distribution {
+    fn add_exploding_style_to(&self, output: &mut Distribution) {
         // Compute the exploding roll
-        let mut explosion = Distribution::new();
-        for i in 1..TARGETS {
+        let mut explosion = self.clone();
+        for i in 1..self.ccdf.len() {
             explosion.ccdf[i] = if i >= 18 { self.ccdf[i - 18] } else { 1.0 };
         }
-        explosion = explosion.add_die(2, false).add_die(20, false);
+        explosion.mean = self.mean + 18.0;
+        let mut rolled = self.clone();
+        explosion.add_die_to(2, false, &mut rolled);
+        rolled.add_die_to(20, false, &mut explosion);
 
         // Compute the base roll
-        let mut output = self.add_die(18, false);
+        self.add_die_to(18, false, output);
 
         // Merge the base and exploding rolls
-        for i in 1..TARGETS {
+        for i in 1..self.ccdf.len() {
             output.ccdf[i] = 0.9 * output.ccdf[i] + 0.1 * explosion.ccdf[i];
         }
-
-        output
+        output.mean = 0.9 * output.mean + 0.1 * explosion.mean;
     }
 }
 
@@ -114,6 +167,20 @@ impl Distribution {
 mod tests {
     use super::*;
 
+    impl Distribution {
+        fn add_die(&self, range: u32, strength: bool) -> Distribution {
+            let mut output = self.clone();
+            self.add_die_to(range, strength, &mut output);
+            output
+        }
+
+        fn add_exploding_style(&self) -> Distribution {
+            let mut output = self.clone();
+            self.add_exploding_style_to(&mut output);
+            output
+        }
+    }
+
     #[test]
     fn test_new() {
         let dist = Distribution::new();
@@ -128,6 +195,7 @@ mod tests {
         assert_eq!(dist.at_least(2), 0.875);
         assert_eq!(dist.at_least(8), 0.125);
         assert_eq!(dist.at_least(9), 0.0);
+        assert_eq!(dist.mean(), 4.5);
     }
 
     #[test]
@@ -137,6 +205,7 @@ mod tests {
         assert_eq!(dist.at_least(3), 0.875);
         assert_eq!(dist.at_least(8), 0.25);
         assert_eq!(dist.at_least(9), 0.0);
+        assert_eq!(dist.mean(), 5.375);
     }
 
     #[test]
@@ -146,6 +215,21 @@ mod tests {
         assert_eq!(dist.at_least(19), 0.10);
         assert!((dist.at_least(40) - 0.0025).abs() < 1e6);
         assert_eq!(dist.at_least(41), 0.0);
+        assert!((dist.mean() - 11.55).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_with_precision() {
+        let dist = Distribution::with_precision(200)
+            .add_ability(Ability::Style, Rewards::STYLE_EXPLODING)
+            .add_ability(Ability::Diction, Rewards::NONE);
+        let truncated = Distribution::new()
+            .add_ability(Ability::Style, Rewards::STYLE_EXPLODING)
+            .add_ability(Ability::Diction, Rewards::NONE);
+        for target in 0..DEFAULT_PRECISION {
+            assert_eq!(dist.at_least(target), truncated.at_least(target));
+        }
+        assert_eq!(dist.mean(), truncated.mean());
     }
 
     #[test]
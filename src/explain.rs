@@ -0,0 +1,51 @@
+use crate::{
+    abilities::Abilities, challenges::{Challenge, TARGET_SET}, distributions::Distribution,
+    rewards::Rewards, solver::SolutionVertex, state::GameState,
+};
+
+/// The outlook of a single challenge within a plan.
+#[derive(Debug)]
+pub struct ChallengeExplanation {
+    /// The index of the challenge.
+    pub challenge: usize,
+
+    /// The abilities used on the challenge.
+    pub abilities: Abilities,
+
+    /// The rewards active while attempting the challenge.
+    pub rewards: Rewards,
+
+    /// The probability of passing the challenge.
+    pub proba: f32,
+
+    /// The expected total of the roll.
+    pub expected: f32,
+
+    /// The expected total of the roll minus the target.
+    pub margin: f32,
+}
+
+/// Explains each challenge of a plan, in play order.
+pub fn explain(
+    challenges: &[Challenge],
+    state: &GameState,
+    solution: &SolutionVertex,
+) -> Vec<ChallengeExplanation> {
+    let mut rewards = state.rewards(challenges);
+    let mut output = Vec::with_capacity(solution.order.len());
+    for (idx, abilities) in solution.order.iter().zip(solution.abilities.iter()) {
+        let challenge = &challenges[*idx];
+        let target = TARGET_SET[challenge.target_idx];
+        let distribution = Distribution::of(abilities, rewards);
+        output.push(ChallengeExplanation {
+            challenge: *idx,
+            abilities: *abilities,
+            rewards,
+            proba: distribution.at_least(target),
+            expected: distribution.mean(),
+            margin: distribution.mean() - target as f32,
+        });
+        rewards |= challenge.reward;
+    }
+    output
+}
@@ -0,0 +1,118 @@
+//! Random boards for testing and research.
+
+use std::fmt::Write;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{challenges::TARGET_SET, rewards::Rewards};
+
+/// The number of challenges on a board of the real game.
+pub const CHALLENGES: usize = 11;
+
+/// The first words of the challenge names.
+const ACTIONS: [&str; 12] = [
+    "Eating", "Reading", "Hating on", "Creating", "Acting in", "Beating up", "Sweeping",
+    "Manipulating", "Dancing with", "Painting", "Debating", "Juggling",
+];
+
+/// The last words of the challenge names.
+const SUBJECTS: [&str; 12] = [
+    "TACOs", "Wordy Instructions", "Fighto", "Quickteams", "BillyLive", "WorldKaiju", "Zombjas",
+    "Marketplaces", "Partyhouses", "Paperwork", "Monologues", "Robo Chefs",
+];
+
+/// The ability caps of the challenges, weighted by how often they appear in the game.
+const CAPS: [(u32, u32); 6] = [(3, 3), (4, 2), (5, 2), (8, 1), (10, 1), (15, 2)];
+
+/// The rewards that do not change the odds of the challenges, such as the finale bonuses.
+const OTHER_REWARDS: [&str; 3] = [
+    "One additional Attempt of your most powerful type in Finale",
+    "+10 per die rolled on Finale",
+    "+11% chance to get back lowest Ability used in attempt",
+];
+
+/// The chance that a challenge rewards a modeled bonus rather than a finale bonus, as in the game.
+const MODELED_REWARD_CHANCE: f64 = 8.0 / 11.0;
+
+/// Returns a random board of `challenges` challenges, in the text format read by `parser::parse`.
+///
+/// `difficulty` ranges from 0 to 1. Higher difficulties draw higher targets and lower ability caps,
+/// while 0.5 gives boards similar to the game's. Each modeled reward appears at most once.
+pub fn generate<R: Rng>(rng: &mut R, challenges: usize, difficulty: f32) -> String {
+    let difficulty = difficulty.clamp(0.0, 1.0);
+    let mut names = Vec::new();
+    let mut rewards = Rewards::all().descriptions();
+    rewards.shuffle(rng);
+
+    let mut output = String::new();
+    for _ in 0..challenges {
+        // Draw a name that is not on the board yet.
+        let mut name = String::new();
+        for attempt in 0.. {
+            let action = ACTIONS.choose(rng).unwrap();
+            let subject = SUBJECTS.choose(rng).unwrap();
+            name = if attempt < 100 {
+                format!("{} {}", action, subject)
+            } else {
+                format!("{} {} {}", action, subject, attempt)
+            };
+            if !names.contains(&name) {
+                break;
+            }
+        }
+        names.push(name.clone());
+
+        // Center the targets on the middle of the set, shifted by the difficulty.
+        let center = difficulty * (TARGET_SET.len() - 1) as f32;
+        let target_idx = (center + rng.gen_range(-3.0..=3.0)).round() as isize;
+        let target = TARGET_SET[target_idx.clamp(0, TARGET_SET.len() as isize - 1) as usize];
+
+        // Lower the caps of harder boards.
+        let (mut cap, _) = *CAPS.choose_weighted(rng, |(_, weight)| *weight).unwrap();
+        if rng.gen::<f32>() < (difficulty - 0.5) * 2.0 {
+            cap = (cap - 1).max(3);
+        } else if rng.gen::<f32>() < (0.5 - difficulty) * 2.0 {
+            cap += 1;
+        }
+
+        let reward = match rewards.last() {
+            Some(_) if rng.gen_bool(MODELED_REWARD_CHANCE) => rewards.pop().unwrap(),
+            _ => OTHER_REWARDS.choose(rng).unwrap(),
+        };
+
+        let line = format!("  {}  \t  Target: {}  \t  Max Abilities: {}", name, target, cap);
+        writeln!(output, "{}", line).unwrap();
+        writeln!(output, "Reward: {}", reward).unwrap();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::parser::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let board = generate(&mut StdRng::seed_from_u64(0), CHALLENGES, 0.5);
+        assert_eq!(board, generate(&mut StdRng::seed_from_u64(0), CHALLENGES, 0.5));
+        let challenges = parse(&board).unwrap();
+        assert_eq!(challenges.len(), CHALLENGES);
+        // Every modeled reward appears at most once.
+        for description in Rewards::all().descriptions() {
+            let reward: Rewards = description.parse().unwrap();
+            assert!(challenges.iter().filter(|c| c.reward == reward).count() <= 1);
+        }
+
+        // Harder boards have higher targets.
+        let mean_target = |difficulty| {
+            let mut rng = StdRng::seed_from_u64(1);
+            let challenges = parse(generate(&mut rng, 200, difficulty)).unwrap();
+            challenges.iter().map(|c| TARGET_SET[c.target_idx]).sum::<usize>() / challenges.len()
+        };
+        assert!(mean_target(0.2) < mean_target(0.8));
+    }
+}
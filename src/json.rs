@@ -0,0 +1,288 @@
+//! Structured output of the solver.
+//!
+//! The frontier is written as a single JSON object:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "frontier": [
+//!     {
+//!       "cost": 800,
+//!       "probability": 0.3303,
+//!       "order": ["Paperwork Montage", "Creating Quickteams"],
+//!       "abilities": [{"Rhythm": 5}, {"Diction": 4}],
+//!       "totals": {"Diction": 4, "Rhythm": 5}
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Frontier points are sorted by increasing cost and probability. `order` lists the challenges by
+//! name in play order, and `abilities[i]` holds the dice used on `order[i]`, as a map from ability
+//! name to count that omits unused abilities. `alternatives` holds other plans within the same
+//! budget, sorted by decreasing probability, and is omitted when empty. When another objective
+//! than passing every challenge was maximized, each plan also holds its `expected_value`.
+//!
+//! Boards are read from and written as a JSON object:
+//!
+//! ```json
+//! {
+//!   "challenges": [
+//!     {"name": "Paperwork Montage", "target": 70, "abilities": 5, "reward": "+1 Diction Strength"}
+//!   ],
+//!   "state": {"completed": ["Paperwork Montage"], "dice": {"Diction": 10}, "points": 24}
+//! }
+//! ```
+//!
+//! `target` must be one of the challenge targets, and `reward` uses the same descriptions as the
+//! text format. `state` is optional, and so are each of its fields. Without `points`, the budget is
+//! unlimited.
+
+use std::ops::Add;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    abilities::Abilities,
+    challenges::{Challenge, TARGET_SET},
+    solver::SolutionVertex,
+    state::GameState,
+};
+
+/// The version of the output schema. Bump this whenever a field is removed or changes meaning.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The solution frontier.
+#[derive(Serialize)]
+pub struct Frontier {
+    /// The version of the output schema.
+    pub version: u32,
+
+    /// The best plans, sorted by increasing cost.
+    pub frontier: Vec<FrontierPoint>,
+}
+
+/// The best plan at a budget, along with its alternatives.
+#[derive(Serialize)]
+pub struct FrontierPoint {
+    #[serde(flatten)]
+    pub plan: Plan,
+
+    /// Other plans within the same budget, sorted by decreasing probability.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<Plan>,
+}
+
+/// A challenge order and ability setup.
+#[derive(Serialize)]
+pub struct Plan {
+    /// The points spent buying the abilities that are not owned. Without owned dice, this is the
+    /// total cost of the abilities.
+    pub cost: u32,
+
+    /// The probability of passing every challenge.
+    pub probability: f32,
+
+    /// The value of the maximized objective, if it was not the probability.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_value: Option<f32>,
+
+    /// The challenge names in play order.
+    pub order: Vec<String>,
+
+    /// The abilities used on each challenge, in play order.
+    pub abilities: Vec<Abilities>,
+
+    /// The abilities used over all challenges.
+    pub totals: Abilities,
+}
+
+impl Plan {
+    /// Returns the structured form of a solution.
+    pub fn new(challenges: &[Challenge], solution: &SolutionVertex) -> Plan {
+        Plan {
+            cost: solution.cost,
+            probability: solution.log_proba.exp(),
+            expected_value: None,
+            order: solution.order.iter().map(|idx| challenges[*idx].name.clone()).collect(),
+            abilities: solution.abilities.clone(),
+            totals: solution.abilities.iter().fold(Abilities::new(), Abilities::add),
+        }
+    }
+}
+
+impl Frontier {
+    /// Returns the structured form of a frontier, where each entry holds the plans at one budget.
+    pub fn new(challenges: &[Challenge], frontier: &[Vec<SolutionVertex>]) -> Frontier {
+        Frontier {
+            version: SCHEMA_VERSION,
+            frontier: frontier
+                .iter()
+                .map(|plans| FrontierPoint {
+                    plan: Plan::new(challenges, &plans[0]),
+                    alternatives: plans[1..].iter().map(|p| Plan::new(challenges, p)).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Fills in the expected value of every plan, for frontiers maximizing another objective.
+    pub fn with_expected_values(mut self, frontier: &[Vec<SolutionVertex>]) -> Frontier {
+        for (point, plans) in self.frontier.iter_mut().zip(frontier) {
+            point.plan.expected_value = Some(plans[0].value);
+            for (plan, solution) in point.alternatives.iter_mut().zip(&plans[1..]) {
+                plan.expected_value = Some(solution.value);
+            }
+        }
+        self
+    }
+}
+
+/// A board, along with the state of a game in progress.
+#[derive(Serialize, Deserialize)]
+pub struct Board {
+    /// The challenges of the board.
+    pub challenges: Vec<BoardChallenge>,
+
+    /// The state of a game in progress.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<BoardState>,
+}
+
+/// A challenge of a board.
+#[derive(Serialize, Deserialize)]
+pub struct BoardChallenge {
+    /// The name of the challenge.
+    pub name: String,
+
+    /// The minimum roll to pass the challenge.
+    pub target: usize,
+
+    /// The maximum number of abilities.
+    pub abilities: u32,
+
+    /// The description of the reward.
+    pub reward: String,
+}
+
+/// The state of a game in progress.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoardState {
+    /// The names of the challenges that have already been passed.
+    pub completed: Vec<String>,
+
+    /// The abilities that are still owned.
+    pub dice: Abilities,
+
+    /// The points left to buy additional abilities with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub points: Option<u32>,
+}
+
+impl Board {
+    /// Returns the structured form of a board.
+    pub fn new(challenges: &[Challenge], state: &GameState) -> Board {
+        Board {
+            challenges: challenges
+                .iter()
+                .map(|challenge| BoardChallenge {
+                    name: challenge.name.clone(),
+                    target: TARGET_SET[challenge.target_idx],
+                    abilities: challenge.abilities,
+                    reward: challenge.reward.to_string(),
+                })
+                .collect(),
+            state: Some(BoardState {
+                completed: state.completed.iter().map(|idx| challenges[*idx].name.clone()).collect(),
+                dice: state.dice,
+                points: state.points,
+            }),
+        }
+    }
+
+    /// Returns the challenges and game state of the board.
+    ///
+    /// Returns `None` if a target is not a challenge target, or a completed challenge is unknown.
+    pub fn parse(&self) -> Option<(Vec<Challenge>, GameState)> {
+        let mut challenges = Vec::with_capacity(self.challenges.len());
+        for challenge in &self.challenges {
+            challenges.push(Challenge {
+                name: challenge.name.clone(),
+                target_idx: TARGET_SET.iter().position(|t| *t == challenge.target)?,
+                abilities: challenge.abilities,
+                reward: challenge.reward.parse().ok()?,
+            });
+        }
+        let mut state = GameState::new();
+        if let Some(board_state) = &self.state {
+            for name in &board_state.completed {
+                state.completed.push(challenges.iter().position(|c| &c.name == name)?);
+            }
+            state.dice = board_state.dice;
+            state.points = board_state.points;
+        }
+        Some((challenges, state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{abilities::Ability, rewards::Rewards};
+
+    use super::*;
+
+    #[test]
+    fn test_frontier() {
+        let challenge = |name: &str| Challenge {
+            name: name.to_string(),
+            target_idx: 0,
+            abilities: 4,
+            reward: Rewards::NONE,
+        };
+        let challenges = vec![challenge("First"), challenge("Second")];
+        let mut rhythm = Abilities::new();
+        rhythm[Ability::Rhythm] = 2;
+        let solution = SolutionVertex {
+            cost: 60,
+            log_proba: 0.0,
+            value: 0.0,
+            order: vec![1, 0],
+            abilities: vec![rhythm, Abilities::new()],
+        };
+        let output = serde_json::to_string(&Frontier::new(&challenges, &[vec![solution]])).unwrap();
+        assert_eq!(
+            output,
+            concat!(
+                r#"{"version":1,"frontier":[{"cost":60,"probability":1.0,"order":["Second","First"],"#,
+                r#""abilities":[{"Rhythm":2},{}],"totals":{"Rhythm":2}}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_board() {
+        let input = r#"{
+            "challenges": [
+                {"name": "First", "target": 70, "abilities": 5, "reward": "+1 Diction Strength"},
+                {"name": "Second", "target": 10, "abilities": 4, "reward": "None"}
+            ],
+            "state": {"completed": ["First"], "dice": {"Diction": 2}}
+        }"#;
+        let board: Board = serde_json::from_str(input).unwrap();
+        let (challenges, state) = board.parse().unwrap();
+        assert_eq!(challenges[0].target_idx, TARGET_SET.len() - 1);
+        assert_eq!(challenges[0].reward, Rewards::DICTION_STRENGTH);
+        assert_eq!(state.completed, vec![0]);
+        assert_eq!(state.dice[Ability::Diction], 2);
+        assert_eq!(state.points, None);
+
+        let output = serde_json::to_string(&Board::new(&challenges, &state)).unwrap();
+        let (reparsed, _) = serde_json::from_str::<Board>(&output).unwrap().parse().unwrap();
+        assert_eq!(reparsed, challenges);
+
+        let mut unknown: Board = serde_json::from_str(input).unwrap();
+        unknown.challenges[1].target = 12;
+        assert!(unknown.parse().is_none());
+    }
+}
@@ -0,0 +1,43 @@
+//! Computes optimal ability setups for the BillyTV dice game.
+//!
+//! A board is a list of [`Challenge`]s, usually read with [`parser::parse`]. A [`Solver`] loads
+//! the challenge setups, which only depend on the rules of the game, and returns the best
+//! [`SolutionVertex`] at every budget:
+//!
+//! ```no_run
+//! use btv::{parser, Solver};
+//!
+//! let challenges = parser::parse(std::fs::read_to_string("board.txt").unwrap()).unwrap();
+//! let solver = Solver::builder().min_proba(0.05).build().unwrap();
+//! for solution in solver.solve(&challenges) {
+//!     println!("{}: {}", solution.cost, solution.log_proba.exp());
+//! }
+//! ```
+
+#[macro_use]
+extern crate bitflags;
+
+pub mod abilities;
+pub mod challenges;
+pub mod constraints;
+pub mod csv;
+pub mod distributions;
+pub mod explain;
+pub mod generator;
+pub mod json;
+pub mod parser;
+pub mod plot;
+pub mod rewards;
+pub mod simulator;
+pub mod solver;
+pub mod state;
+
+pub use crate::{
+    abilities::{Abilities, Ability},
+    challenges::Challenge,
+    constraints::Constraint,
+    distributions::Distribution,
+    rewards::Rewards,
+    solver::{Objective, SolutionVertex, Solver, SolverBuilder, SolverConfig},
+    state::GameState,
+};
@@ -1,56 +1,73 @@
-#[macro_use]
-extern crate bitflags;
+mod commands;
 
-mod abilities;
-mod challenges;
-mod distributions;
-mod parser;
-mod rewards;
-mod solver;
+use std::env;
 
-use std::{fs::File, io::Read, ops::Add, path::Path};
+use clap::{AppSettings, Clap, IntoApp};
 
-use clap::{AppSettings, Clap};
-
-use crate::abilities::Abilities;
+use crate::commands::{
+    analyze::AnalyzeArgs, batch::BatchArgs, explain::ExplainArgs, generate::GenerateArgs,
+    play::PlayArgs, plot::PlotArgs, report::ReportArgs, research::ResearchArgs, serve::ServeArgs,
+    simulate::SimulateArgs, solve::SolveArgs,
+};
 
 #[derive(Clap)]
 #[clap(version="1.0", author="Joshua Chin")]
 #[clap(setting = AppSettings::ColoredHelp)]
 struct Args {
-    /// The path of the input file.
-    input: String,
+    #[clap(subcommand)]
+    command: Command,
 }
 
-fn main() {
-    let args = Args::parse();
+#[derive(Clap)]
+enum Command {
+    /// Prints the optimal plans at every budget.
+    Solve(SolveArgs),
+    /// Prints the marginal value of each reward, target and extra points.
+    Analyze(AnalyzeArgs),
+    /// Explains each challenge of the best plan at a budget.
+    Explain(ExplainArgs),
+    /// Plays the best plan at a budget many times to check the predicted success chance.
+    Simulate(SimulateArgs),
+    /// Suggests each challenge during a live game and re-plans after every attempt.
+    Play(PlayArgs),
+    /// Serves the solver as a JSON API on localhost.
+    Serve(ServeArgs),
+    /// Writes an SVG chart of the success chance against cost for one or more boards.
+    Plot(PlotArgs),
+    /// Writes a Markdown or HTML report of the best plan at a budget.
+    Report(ReportArgs),
+    /// Solves many boards and prints a summary table.
+    Batch(BatchArgs),
+    /// Generates random boards in the input format.
+    Generate(GenerateArgs),
+    /// Solves many boards and aggregates the abilities bought, the reward order and the success
+    /// chances by budget.
+    Research(ResearchArgs),
+}
 
-    let input = {
-        let path = Path::new(&args.input);
-        let mut file = File::open(path).unwrap();
-        let mut input= String::new();
-        file.read_to_string(&mut input).unwrap();
-        input
-    };
+/// The arguments that are handled without a subcommand.
+const TOP_LEVEL_ARGS: [&str; 5] = ["help", "-h", "--help", "-V", "--version"];
 
-    println!("Parsing challenges");
-    let challenges = parser::parse(input).unwrap();
-    
-    let solutions = solver::solve(&challenges);
-    
-    for solution in solutions {
-        let proba = solution.log_proba.exp();
-        if proba < 0.1 {
-            continue;
-        }
-        println!("Cost: {}", solution.cost);
-        println!("Success chance: {}", proba);
-        println!("Order: {:?}", solution.order);
-        println!("Totals: {:?}", solution.abilities.iter().fold(Abilities::new(), Abilities::add));
-        println!("Abilities:");
-        for abilities in solution.abilities {
-            println!("{:?}", abilities);
+fn main() {
+    // `btv <input> [options]` solves the board, as it did before the subcommands were added.
+    let mut args: Vec<String> = env::args().collect();
+    if let Some(first) = args.get(1).map(String::as_str) {
+        let subcommand = Args::into_app().find_subcommand(first).is_some();
+        if !subcommand && !TOP_LEVEL_ARGS.contains(&first) {
+            args.insert(1, "solve".to_string());
         }
-        println!();
+    }
+    match Args::parse_from(args).command {
+        Command::Solve(args) => commands::solve::run(args),
+        Command::Analyze(args) => commands::analyze::run(args),
+        Command::Explain(args) => commands::explain::run(args),
+        Command::Simulate(args) => commands::simulate::run(args),
+        Command::Play(args) => commands::play::run(args),
+        Command::Serve(args) => commands::serve::run(args),
+        Command::Plot(args) => commands::plot::run(args),
+        Command::Report(args) => commands::report::run(args),
+        Command::Batch(args) => commands::batch::run(args),
+        Command::Generate(args) => commands::generate::run(args),
+        Command::Research(args) => commands::research::run(args),
     }
 }
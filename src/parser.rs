@@ -1,49 +1,237 @@
-use crate::{challenges::Challenge, rewards::Rewards, challenges::TARGET_SET};
+use crate::{abilities::Ability, challenges::Challenge, constraints::Constraint, rewards::Rewards, challenges::TARGET_SET, state::GameState};
 
 /// Parses a string containing multiple challenges.
-pub fn parse<T: AsRef<str>>(text: T) -> Option<Vec<Challenge>> {
+///
+/// Returns an error naming the first line that cannot be parsed.
+pub fn parse<T: AsRef<str>>(text: T) -> Result<Vec<Challenge>, String> {
     let mut output = Vec::with_capacity(11);
 
-    for entry in text.as_ref().lines().collect::<Vec<&str>>().chunks_exact(2) {
-        // Parse the first line
-        let mut l1 = entry[0].split('\t');
-        // Parse name
-        let name = l1.next()?.trim().to_string();
-        // Parse target
-        let target: usize = l1.next()?.trim().strip_prefix("Target: ")?.parse().ok()?;
-        let target_idx = TARGET_SET.iter().position(|t| *t == target)?;
-        // Parse abilities
-        let abilities: u32 = l1
-            .next()?
-            .trim()
-            .strip_prefix("Max Abilities: ")?
-            .parse()
-            .ok()?;
+    let lines: Vec<&str> = text.as_ref().lines().collect();
+    let entries = lines.chunks_exact(2);
+    // A challenge cut short on the last line.
+    if let Some(line) = entries.remainder().first().filter(|line| !line.trim().is_empty()) {
+        return Err(invalid_line(lines.len() - 1, line));
+    }
+    for (idx, entry) in entries.enumerate() {
         // Parse the reward
-        let reward: Rewards = entry[1].trim().strip_prefix("Reward: ")?.parse().ok()?;
+        let reward: Rewards = entry[1]
+            .trim()
+            .strip_prefix("Reward: ")
+            .and_then(|reward| reward.parse().ok())
+            .ok_or_else(|| invalid_line(2 * idx + 1, entry[1]))?;
+        let challenge =
+            parse_challenge(entry[0], reward).ok_or_else(|| invalid_line(2 * idx, entry[0]))?;
+        output.push(challenge);
+    }
+
+    Ok(output)
+}
+
+/// Parses the first line of a challenge, given its reward.
+fn parse_challenge(first: &str, reward: Rewards) -> Option<Challenge> {
+    // Parse the first line
+    let mut l1 = first.split('\t');
+    // Parse name
+    let name = l1.next()?.trim().to_string();
+    // Parse target
+    let target: usize = l1.next()?.trim().strip_prefix("Target: ")?.parse().ok()?;
+    let target_idx = TARGET_SET.iter().position(|t| *t == target)?;
+    // Parse abilities
+    let abilities: u32 = l1
+        .next()?
+        .trim()
+        .strip_prefix("Max Abilities: ")?
+        .parse()
+        .ok()?;
+
+    Some(Challenge {
+        name,
+        target_idx,
+        abilities,
+        reward,
+    })
+}
+
+/// Returns the error message of a line that cannot be parsed, given its index.
+fn invalid_line(idx: usize, line: &str) -> String {
+    format!("Invalid line {}: {}", idx + 1, line.trim())
+}
+
+/// Returns the non-empty lines of a string, trimmed, along with their indices.
+fn content_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines().map(str::trim).enumerate().filter(|(_, line)| !line.is_empty())
+}
+
+/// Parses a string containing the state of a game in progress.
+///
+/// Each line holds one field, for example:
+///
+/// ```text
+/// Completed: Monologue, Eating TACOs
+/// Dice: Atmosphere 2, Diction 10, Rhythm 4
+/// Points: 24
+/// ```
+///
+/// Completed challenges are matched by name against `challenges`. Missing fields are left empty,
+/// except for the points which default to zero. Returns an error naming the first line that cannot
+/// be parsed.
+pub fn parse_state<T: AsRef<str>>(text: T, challenges: &[Challenge]) -> Result<GameState, String> {
+    let mut state = GameState {
+        points: Some(0),
+        ..GameState::new()
+    };
+
+    for (idx, line) in content_lines(text.as_ref()) {
+        parse_state_line(line, challenges, &mut state).ok_or_else(|| invalid_line(idx, line))?;
+    }
+
+    Ok(state)
+}
+
+/// Parses a line of the state of a game in progress into `state`.
+fn parse_state_line(line: &str, challenges: &[Challenge], state: &mut GameState) -> Option<()> {
+    let (key, value) = line.split_once(':')?;
+    let items = value.split(',').map(str::trim).filter(|v| !v.is_empty());
+    match key.trim() {
+        "Completed" => {
+            for name in items {
+                let idx = challenges.iter().position(|c| c.name == name)?;
+                state.completed.push(idx);
+            }
+        }
+        "Dice" => {
+            for item in items {
+                let (ability, count) = item.split_once(' ')?;
+                let ability: Ability = ability.parse().ok()?;
+                let count = count.trim().parse::<u8>().ok()?;
+                state.dice[ability] = state.dice[ability].checked_add(count)?;
+            }
+        }
+        "Points" => state.points = Some(value.trim().parse().ok()?),
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Parses a string containing constraints on the challenge order.
+///
+/// Each line holds one constraint, for example:
+///
+/// ```text
+/// Pin: Beating up WorldKaiju, 1
+/// Before: Hating on Fighto, Reading Wordy Instructions
+/// Exclude: Eating TACOs
+/// ```
+///
+/// Challenges are matched by name against `challenges`, and positions count from one. Returns an
+/// error naming the first line that cannot be parsed.
+pub fn parse_constraints<T: AsRef<str>>(
+    text: T,
+    challenges: &[Challenge],
+) -> Result<Vec<Constraint>, String> {
+    let mut output = Vec::new();
+
+    for (idx, line) in content_lines(text.as_ref()) {
+        let constraint =
+            parse_constraint(line, challenges, &output).ok_or_else(|| invalid_line(idx, line))?;
+        output.push(constraint);
+    }
+
+    Ok(output)
+}
+
+/// Parses a constraint, given the constraints on the previous lines.
+fn parse_constraint(
+    line: &str,
+    challenges: &[Challenge],
+    previous: &[Constraint],
+) -> Option<Constraint> {
+    let find = |name: &str| challenges.iter().position(|c| c.name == name.trim());
+    let (key, value) = line.split_once(':')?;
+    let constraint = match key.trim() {
+        "Pin" => {
+            let (name, position) = value.rsplit_once(',')?;
+            let position: usize = position.trim().parse().ok()?;
+            if position == 0 || position > challenges.len() {
+                return None;
+            }
+            let (challenge, position) = (find(name)?, position - 1);
+            // A challenge fills one position, and each position holds one challenge.
+            let conflicts = previous.iter().any(|constraint| {
+                matches!(*constraint, Constraint::Pin { challenge: c, position: p }
+                    if c == challenge || p == position)
+            });
+            if conflicts {
+                return None;
+            }
+            Constraint::Pin { challenge, position }
+        }
+        "Before" => {
+            let (first, second) = value.split_once(',')?;
+            Constraint::Before {
+                first: find(first)?,
+                second: find(second)?,
+            }
+        }
+        "Exclude" => Constraint::Exclude {
+            challenge: find(value)?,
+        },
+        _ => return None,
+    };
+    Some(constraint)
+}
 
-        output.push(Challenge {
-            name,
-            target_idx,
-            abilities,
-            reward,
-        });
+/// Parses a string containing the value of passing each challenge.
+///
+/// Each line holds the name of a challenge and its value, for example:
+///
+/// ```text
+/// Eating TACOs: 3
+/// Beating up WorldKaiju: 0.5
+/// ```
+///
+/// Challenges are matched by name against `challenges`. Challenges that are not listed are worth
+/// one. Values must be finite and not negative. Returns an error naming the first line that cannot
+/// be parsed.
+pub fn parse_values<T: AsRef<str>>(text: T, challenges: &[Challenge]) -> Result<Vec<f32>, String> {
+    let mut output = vec![1.0; challenges.len()];
+
+    for (idx, line) in content_lines(text.as_ref()) {
+        let (challenge, value) =
+            parse_value(line, challenges).ok_or_else(|| invalid_line(idx, line))?;
+        output[challenge] = value;
     }
 
-    Some(output)
+    Ok(output)
+}
+
+/// Parses the index of a challenge and its value.
+fn parse_value(line: &str, challenges: &[Challenge]) -> Option<(usize, f32)> {
+    let (name, value) = line.rsplit_once(':')?;
+    let idx = challenges.iter().position(|c| c.name == name.trim())?;
+    let value: f32 = value.trim().parse().ok()?;
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+    Some((idx, value))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::abilities::Abilities;
+
     use super::*;
 
     #[test]
     fn test_parse() {
         let input = include_str!("test_input.txt");
         let result = parse(input);
-        assert!(result.is_some());
+        assert!(result.is_ok());
         let challenges = result.unwrap();
         assert!(challenges.len() == 11);
+        let invalid = input.replacen("Target: 30", "Target: 31", 1);
+        assert!(parse(invalid).unwrap_err().starts_with("Invalid line 1: Monologue"));
+        assert_eq!(parse("Monologue"), Err("Invalid line 1: Monologue".to_string()));
         println!("{:?}", challenges);
         assert_eq!(
             challenges[0],
@@ -55,4 +243,64 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_parse_state() {
+        let challenges = parse(include_str!("test_input.txt")).unwrap();
+        let input = "Completed: Monologue, Eating TACOs\nDice: Diction 10, Rhythm 4\n\nPoints: 24\n";
+        let state = parse_state(input, &challenges).unwrap();
+        let mut dice = Abilities::new();
+        dice[Ability::Diction] = 10;
+        dice[Ability::Rhythm] = 4;
+        assert_eq!(
+            state,
+            GameState {
+                completed: vec![0, 3],
+                dice,
+                points: Some(24),
+            }
+        );
+        assert_eq!(state.rewards(&challenges), Rewards::DICTION_RANGE);
+        assert_eq!(
+            parse_state("Points: 3\n\nCompleted: Unknown", &challenges),
+            Err("Invalid line 3: Completed: Unknown".to_string())
+        );
+        assert!(parse_state("Dice: Timing 200, Timing 200", &challenges).is_err());
+    }
+
+    #[test]
+    fn test_parse_constraints() {
+        let challenges = parse(include_str!("test_input.txt")).unwrap();
+        let input = "Pin: Beating up WorldKaiju, 1\nBefore: Monologue, Eating TACOs\nExclude: Zombja Sweeping\n";
+        assert_eq!(
+            parse_constraints(input, &challenges).unwrap(),
+            vec![
+                Constraint::Pin { challenge: 10, position: 0 },
+                Constraint::Before { first: 0, second: 3 },
+                Constraint::Exclude { challenge: 7 },
+            ]
+        );
+        assert!(parse_constraints("Pin: Monologue, 12", &challenges).is_err());
+        assert_eq!(
+            parse_constraints("Pin: Monologue, 1\nPin: Monologue, 2", &challenges),
+            Err("Invalid line 2: Pin: Monologue, 2".to_string())
+        );
+        assert!(parse_constraints("Pin: Monologue, 1\nPin: Eating TACOs, 1", &challenges).is_err());
+    }
+
+    #[test]
+    fn test_parse_values() {
+        let challenges = parse(include_str!("test_input.txt")).unwrap();
+        let values = parse_values("Eating TACOs: 3\nMonologue: 0.5\n", &challenges).unwrap();
+        assert_eq!(values[3], 3.0);
+        assert_eq!(values[0], 0.5);
+        assert_eq!(values[1], 1.0);
+        assert_eq!(
+            parse_values("Monologue: 2\nUnknown: 1", &challenges),
+            Err("Invalid line 2: Unknown: 1".to_string())
+        );
+        assert!(parse_values("Monologue: NaN", &challenges).is_err());
+        assert!(parse_values("Monologue: inf", &challenges).is_err());
+        assert!(parse_values("Monologue: -1", &challenges).is_err());
+    }
 }
@@ -0,0 +1,303 @@
+//! Charts of the success chance against the cost of the abilities.
+
+use std::fmt::Write;
+
+use crate::solver::SolutionVertex;
+
+/// The width of the SVG chart, in pixels.
+const WIDTH: f32 = 800.0;
+
+/// The height of the SVG chart, in pixels.
+const HEIGHT: f32 = 500.0;
+
+/// The margins around the plot area, in pixels: left, right, top and bottom.
+const MARGINS: (f32, f32, f32, f32) = (60.0, 200.0, 20.0, 50.0);
+
+/// The colors of the series, reused when there are more series than colors.
+const COLORS: [&str; 6] = ["#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b"];
+
+/// A frontier to plot, as (cost, probability) points sorted by increasing cost.
+#[derive(Clone, Debug)]
+pub struct Series {
+    /// The name shown in the legend.
+    pub label: String,
+
+    /// The best success chance at each cost where it improves.
+    pub points: Vec<(u32, f32)>,
+}
+
+impl Series {
+    /// Returns the series of a frontier.
+    pub fn new<T: Into<String>>(label: T, frontier: &[SolutionVertex]) -> Series {
+        Series {
+            label: label.into(),
+            points: frontier.iter().map(|v| (v.cost, v.log_proba.exp())).collect(),
+        }
+    }
+
+    /// Returns the vertices of the upper concave hull of the points.
+    ///
+    /// Between two consecutive vertices, each extra point buys the same success chance. The slope
+    /// drops at every vertex, so these are the costs beyond which extra points pay off less.
+    pub fn hull(&self) -> Vec<(u32, f32)> {
+        let mut hull: Vec<(u32, f32)> = Vec::new();
+        for &(cost, proba) in &self.points {
+            while hull.len() >= 2 {
+                let (c1, p1) = hull[hull.len() - 2];
+                let (c2, p2) = hull[hull.len() - 1];
+                // Drop the last vertex if it lies on or below the line to the new point.
+                let cross = (c2 - c1) as f32 * (proba - p1) - (p2 - p1) * (cost - c1) as f32;
+                if cross < 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push((cost, proba));
+        }
+        hull
+    }
+}
+
+/// Returns a self-contained SVG chart of the series, marking the hull vertices and the budget.
+///
+/// Each series is drawn as a step line, since the best plan within a budget is the last frontier
+/// point that costs at most the budget.
+pub fn svg(series: &[Series], budget: Option<u32>) -> String {
+    let (left, right, top, bottom) = MARGINS;
+    let (plot_width, plot_height) = (WIDTH - left - right, HEIGHT - top - bottom);
+
+    // Round the cost range out to multiples of the tick step.
+    let costs = series.iter().flat_map(|s| s.points.iter().map(|(cost, _)| *cost)).chain(budget);
+    let (min_cost, max_cost) = costs.fold((u32::MAX, 0), |(lo, hi), c| (lo.min(c), hi.max(c)));
+    let (min_cost, max_cost) = if min_cost > max_cost { (0, 100) } else { (min_cost, max_cost) };
+    let step = tick_step(max_cost - min_cost);
+    let min_cost = min_cost / step * step;
+    let max_cost = (max_cost.div_ceil(step) * step).max(min_cost + step);
+
+    let x = |cost: u32| left + (cost - min_cost) as f32 / (max_cost - min_cost) as f32 * plot_width;
+    let y = |proba: f32| top + (1.0 - proba) * plot_height;
+
+    let mut output = String::new();
+    writeln!(
+        output,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">"#,
+        w = WIDTH,
+        h = HEIGHT
+    )
+    .unwrap();
+    writeln!(output, r#"<rect width="{}" height="{}" fill="white"/>"#, WIDTH, HEIGHT).unwrap();
+
+    // Grid lines and axis labels.
+    for tenth in 0..=10 {
+        let proba = tenth as f32 / 10.0;
+        writeln!(
+            output,
+            r##"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="#ddd"/><text x="{}" y="{}" text-anchor="end">{}</text>"##,
+            left,
+            left + plot_width,
+            left - 6.0,
+            y(proba) + 4.0,
+            proba,
+            y = y(proba)
+        )
+        .unwrap();
+    }
+    for cost in (min_cost..=max_cost).step_by(step as usize) {
+        writeln!(
+            output,
+            r##"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="#ddd"/><text x="{x}" y="{}" text-anchor="middle">{}</text>"##,
+            top,
+            top + plot_height,
+            top + plot_height + 16.0,
+            cost,
+            x = x(cost)
+        )
+        .unwrap();
+    }
+    writeln!(
+        output,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="black"/>"#,
+        left, top, plot_width, plot_height
+    )
+    .unwrap();
+    writeln!(
+        output,
+        r#"<text x="{}" y="{}" text-anchor="middle">Cost</text>"#,
+        left + plot_width / 2.0,
+        HEIGHT - 10.0
+    )
+    .unwrap();
+    writeln!(
+        output,
+        r#"<text transform="translate(16 {}) rotate(-90)" text-anchor="middle">Success chance</text>"#,
+        top + plot_height / 2.0
+    )
+    .unwrap();
+
+    // The series, their hull vertices and their legend entries.
+    for (idx, series) in series.iter().enumerate() {
+        let color = COLORS[idx % COLORS.len()];
+        let mut path = String::new();
+        for (point, &(cost, proba)) in series.points.iter().enumerate() {
+            if point == 0 {
+                write!(path, "M{:.1} {:.1}", x(cost), y(proba)).unwrap();
+            } else {
+                write!(path, " H{:.1} V{:.1}", x(cost), y(proba)).unwrap();
+            }
+        }
+        if !series.points.is_empty() {
+            write!(path, " H{:.1}", x(max_cost)).unwrap();
+        }
+        writeln!(output, r#"<path d="{}" fill="none" stroke="{}" stroke-width="2"/>"#, path, color)
+            .unwrap();
+        for (cost, proba) in series.hull() {
+            writeln!(
+                output,
+                r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="{}"><title>{}: {} points, {:.4}</title></circle>"#,
+                x(cost),
+                y(proba),
+                color,
+                escape(&series.label),
+                cost,
+                proba
+            )
+            .unwrap();
+        }
+        let legend_y = top + 10.0 + 20.0 * idx as f32;
+        writeln!(
+            output,
+            r#"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="{}" stroke-width="2"/><text x="{}" y="{}">{}</text>"#,
+            left + plot_width + 15.0,
+            left + plot_width + 35.0,
+            color,
+            left + plot_width + 40.0,
+            legend_y + 4.0,
+            escape(&series.label),
+            y = legend_y
+        )
+        .unwrap();
+    }
+
+    if let Some(budget) = budget {
+        writeln!(
+            output,
+            r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="black" stroke-dasharray="4 4"/><text x="{}" y="{}">Budget {}</text>"#,
+            top,
+            top + plot_height,
+            x(budget) + 4.0,
+            top + 14.0,
+            budget,
+            x = x(budget)
+        )
+        .unwrap();
+    }
+
+    output.push_str("</svg>\n");
+    output
+}
+
+/// The characters of a terminal chart cell, indexed by the filled eighths of the cell.
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The ASCII fallback of `BLOCKS`.
+const ASCII_BLOCKS: [char; 9] = [' ', ' ', '.', '.', ':', ':', '#', '#', '#'];
+
+/// Returns a chart of a series for the terminal, `width` columns of plot area by `height` rows.
+///
+/// Each column shows the best success chance within the cost at its left edge, as a filled bar
+/// with a resolution of an eighth of a row. With `ascii`, only ASCII characters are used.
+pub fn terminal(series: &Series, width: usize, height: usize, ascii: bool) -> String {
+    let (blocks, vertical, corner, horizontal) = if ascii {
+        (ASCII_BLOCKS, '|', '+', '-')
+    } else {
+        (BLOCKS, '│', '└', '─')
+    };
+    let (min_cost, max_cost) = match (series.points.first(), series.points.last()) {
+        (Some(first), Some(last)) => (first.0, last.0.max(first.0 + 1)),
+        _ => return String::new(),
+    };
+    let proba = |cost: f32| {
+        let point = series.points.iter().take_while(|(c, _)| *c as f32 <= cost).last();
+        point.map_or(0.0, |(_, proba)| *proba)
+    };
+    let eighths: Vec<usize> = (0..width)
+        .map(|column| {
+            let fraction = column as f32 / (width.max(2) - 1) as f32;
+            let cost = min_cost as f32 + (max_cost - min_cost) as f32 * fraction;
+            (proba(cost) * (height * 8) as f32).round() as usize
+        })
+        .collect();
+
+    let mut output = String::new();
+    for row in (0..height).rev() {
+        // Label the top, middle and bottom rows with the success chance at their bottom edge.
+        let label = if row == height - 1 || row == 0 || row == height / 2 {
+            format!("{:.2}", row as f32 / height as f32)
+        } else {
+            String::new()
+        };
+        write!(output, "{:>5} {}", label, vertical).unwrap();
+        for eighths in &eighths {
+            output.push(blocks[eighths.saturating_sub(row * 8).min(8)]);
+        }
+        output.push('\n');
+    }
+    writeln!(output, "{:>5} {}{}", "", corner, horizontal.to_string().repeat(width)).unwrap();
+    let max_label = max_cost.to_string();
+    let padding = width.saturating_sub(max_label.len());
+    writeln!(output, "{:>7}{:<w$}{}", "", min_cost, max_label, w = padding).unwrap();
+    output
+}
+
+/// Returns a round distance between cost ticks, giving at most about ten ticks.
+fn tick_step(range: u32) -> u32 {
+    [10, 20, 50, 100, 200, 500, 1000].iter().copied().find(|step| range / step <= 10).unwrap_or(2000)
+}
+
+/// Escapes the characters with a special meaning in XML text.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hull() {
+        let series = Series {
+            label: "board".to_string(),
+            points: vec![(0, 0.0), (10, 0.5), (20, 0.55), (30, 0.9), (40, 0.95), (50, 0.96)],
+        };
+        // (20, 0.55) lies below the line from (10, 0.5) to (30, 0.9).
+        assert_eq!(series.hull(), vec![(0, 0.0), (10, 0.5), (30, 0.9), (40, 0.95), (50, 0.96)]);
+    }
+
+    #[test]
+    fn test_terminal() {
+        let series = Series {
+            label: "board".to_string(),
+            points: vec![(100, 0.5), (200, 1.0)],
+        };
+        let output = terminal(&series, 11, 2, true);
+        let rows: Vec<&str> = output.lines().collect();
+        assert_eq!(rows[0], " 0.50 |          #");
+        assert_eq!(rows[1], " 0.00 |###########");
+        assert_eq!(rows[2], "      +-----------");
+        assert_eq!(rows[3], "       100     200");
+    }
+
+    #[test]
+    fn test_svg() {
+        let series = Series {
+            label: "A & B".to_string(),
+            points: vec![(100, 0.2), (150, 0.6), (220, 0.8)],
+        };
+        let output = svg(&[series], Some(180));
+        assert!(output.starts_with("<svg"));
+        assert!(output.ends_with("</svg>\n"));
+        assert!(output.contains("A &amp; B"));
+        assert!(output.contains("Budget 180"));
+        assert_eq!(output.matches("<circle").count(), 3);
+    }
+}
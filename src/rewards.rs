@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{fmt::{self, Display}, str::FromStr};
 
 
 bitflags! {
@@ -16,7 +16,28 @@ bitflags! {
     }
 }
 
+/// The description of each reward, as shown in the game.
+const DESCRIPTIONS: [(Rewards, &str); 8] = [
+    (Rewards::ADDITIONAL_ABILITY, "+1 Ability max per challenge"),
+    (Rewards::ATMOSPHERE_RANGE, "+1 Atmosphere Range"),
+    (Rewards::DICTION_STRENGTH, "+1 Diction Strength"),
+    (Rewards::DICTION_RANGE, "+1 Diction Range"),
+    (Rewards::PRECISION_STRENGTH, "+1 Precision Strength"),
+    (Rewards::PRECISION_RANGE, "+1 Precision Range"),
+    (Rewards::CALMNESS_STRENGTH, "+1 Calmness Strength"),
+    (Rewards::STYLE_EXPLODING, "+1 Style Attempt on Style roll of 19 or 20"),
+];
+
 impl Rewards {
+    /// Returns the descriptions of the rewards in the set.
+    pub fn descriptions(&self) -> Vec<&'static str> {
+        DESCRIPTIONS
+            .iter()
+            .filter(|(reward, _)| self.contains(*reward))
+            .map(|(_, description)| *description)
+            .collect()
+    }
+
     /// Iterate over the set of all possible reward configurations.
     pub fn combinations() -> impl Iterator<Item = Rewards> {
         (0..256).map(Rewards::from_bits_truncate)
@@ -27,16 +48,19 @@ impl FromStr for Rewards {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "+1 Ability max per challenge" => Rewards::ADDITIONAL_ABILITY,
-            "+1 Atmosphere Range" => Rewards::ATMOSPHERE_RANGE,
-            "+1 Diction Strength" => Rewards::DICTION_STRENGTH,
-            "+1 Diction Range" => Rewards::DICTION_RANGE,
-            "+1 Precision Strength" => Rewards::PRECISION_STRENGTH,
-            "+1 Precision Range" => Rewards::PRECISION_RANGE,
-            "+1 Calmness Strength" => Rewards::CALMNESS_STRENGTH,
-            "+1 Style Attempt on Style roll of 19 or 20" => Rewards::STYLE_EXPLODING,
-            _ => Rewards::NONE
-        })
+        Ok(DESCRIPTIONS
+            .iter()
+            .find(|(_, description)| *description == s)
+            .map_or(Rewards::NONE, |(reward, _)| *reward))
     }
-}
\ No newline at end of file
+}
+
+/// Formats the rewards by their descriptions, separated by commas.
+impl Display for Rewards {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("None");
+        }
+        f.write_str(&self.descriptions().join(", "))
+    }
+}
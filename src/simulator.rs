@@ -0,0 +1,174 @@
+use rand::Rng;
+
+use crate::{
+    abilities::{Abilities, Ability},
+    challenges::{Challenge, TARGET_SET},
+    rewards::Rewards,
+    solver::SolutionVertex,
+    state::GameState,
+};
+
+/// The outcome of playing a plan many times.
+#[derive(Debug)]
+pub struct Simulation {
+    /// The number of games played.
+    pub runs: u32,
+
+    /// The number of games in which every challenge was passed.
+    pub passes: u32,
+
+    /// The number of times each challenge of the plan was attempted, in play order.
+    pub attempts: Vec<u32>,
+
+    /// The number of times each challenge of the plan was passed, in play order.
+    pub challenge_passes: Vec<u32>,
+}
+
+impl Simulation {
+    /// Returns the fraction of games in which every challenge was passed.
+    pub fn pass_rate(&self) -> f64 {
+        self.passes as f64 / self.runs as f64
+    }
+
+    /// Returns the Wilson score interval of the pass rate, given the z-score of the confidence
+    /// level (1.96 for 95%).
+    pub fn confidence_interval(&self, z: f64) -> (f64, f64) {
+        let n = self.runs as f64;
+        let p = self.pass_rate();
+        let denominator = 1.0 + z * z / n;
+        let center = (p + z * z / (2.0 * n)) / denominator;
+        let spread = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+        (center - spread, center + spread)
+    }
+}
+
+/// Plays a plan `runs` times, rolling the dice of each challenge in order.
+///
+/// Rewards apply from the challenge after the one that earned them, and a game ends at the first
+/// failed challenge. At least one game is played, so that the pass rate is defined.
+pub fn simulate<R: Rng>(
+    challenges: &[Challenge],
+    state: &GameState,
+    solution: &SolutionVertex,
+    runs: u32,
+    rng: &mut R,
+) -> Simulation {
+    let runs = runs.max(1);
+    let mut output = Simulation {
+        runs,
+        passes: 0,
+        attempts: vec![0; solution.order.len()],
+        challenge_passes: vec![0; solution.order.len()],
+    };
+    let initial_rewards = state.rewards(challenges);
+
+    for _ in 0..runs {
+        let mut rewards = initial_rewards;
+        let mut passed = true;
+        for (step, (idx, abilities)) in solution.order.iter().zip(&solution.abilities).enumerate() {
+            let challenge = &challenges[*idx];
+            output.attempts[step] += 1;
+            if roll_all(abilities, rewards, rng) < TARGET_SET[challenge.target_idx] as u32 {
+                passed = false;
+                break;
+            }
+            output.challenge_passes[step] += 1;
+            rewards |= challenge.reward;
+        }
+        output.passes += passed as u32;
+    }
+
+    output
+}
+
+/// Rolls every die of the abilities and returns the total.
+fn roll_all<R: Rng>(abilities: &Abilities, rewards: Rewards, rng: &mut R) -> u32 {
+    let mut total = 0;
+    for ability in Ability::values() {
+        for _ in 0..abilities[ability] {
+            total += roll(ability, rewards, rng);
+        }
+    }
+    total
+}
+
+/// Rolls a single ability die.
+///
+/// A range reward adds one face to the die, and a strength reward adds one to the roll, up to the
+/// number of faces. With the exploding Style reward, every Style roll of 19 or 20 grants another
+/// Style roll.
+fn roll<R: Rng>(ability: Ability, rewards: Rewards, rng: &mut R) -> u32 {
+    let (range, strength) = match ability {
+        Ability::Atmosphere => (
+            ability.cost() + rewards.contains(Rewards::ATMOSPHERE_RANGE) as u32,
+            false,
+        ),
+        Ability::Diction => (
+            ability.cost() + rewards.contains(Rewards::DICTION_RANGE) as u32,
+            rewards.contains(Rewards::DICTION_STRENGTH),
+        ),
+        Ability::Precision => (
+            ability.cost() + rewards.contains(Rewards::PRECISION_RANGE) as u32,
+            rewards.contains(Rewards::PRECISION_STRENGTH),
+        ),
+        Ability::Calmness => (ability.cost(), rewards.contains(Rewards::CALMNESS_STRENGTH)),
+        _ => (ability.cost(), false),
+    };
+
+    let value = rng.gen_range(1..=range);
+    let value = if strength { (value + 1).min(range) } else { value };
+
+    match ability {
+        Ability::Style if rewards.contains(Rewards::STYLE_EXPLODING) && value >= 19 => {
+            value + roll(ability, rewards, rng)
+        }
+        _ => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_roll() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let rewards = Rewards::DICTION_RANGE | Rewards::DICTION_STRENGTH;
+            let value = roll(Ability::Diction, rewards, &mut rng);
+            assert!((2..=7).contains(&value));
+            assert!((1..=30).contains(&roll(Ability::Rhythm, Rewards::NONE, &mut rng)));
+        }
+    }
+
+    #[test]
+    fn test_simulate() {
+        let challenges = vec![Challenge {
+            name: "challenge".to_string(),
+            target_idx: 0,
+            abilities: 4,
+            reward: Rewards::NONE,
+        }];
+        let mut abilities = Abilities::new();
+        abilities[Ability::Precision] = 2;
+        let solution = SolutionVertex {
+            cost: 16,
+            log_proba: 0.0,
+            value: 0.0,
+            order: vec![0],
+            abilities: vec![abilities],
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        let simulation = simulate(&challenges, &GameState::new(), &solution, 10000, &mut rng);
+        // Two eight sided dice reach 10 with probability 28 / 64.
+        let (low, high) = simulation.confidence_interval(3.0);
+        assert!(low < 0.4375 && 0.4375 < high);
+        assert_eq!(simulation.attempts[0], 10000);
+
+        let simulation = simulate(&challenges, &GameState::new(), &solution, 0, &mut rng);
+        assert_eq!(simulation.runs, 1);
+        assert!(simulation.pass_rate().is_finite());
+    }
+}
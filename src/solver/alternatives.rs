@@ -0,0 +1,113 @@
+use super::SolutionVertex;
+
+/// The best distinct plans found so far, sorted by decreasing objective value.
+///
+/// Two plans are distinct if they attempt the challenges in a different order or use different
+/// abilities on at least one challenge. Of two identical plans, the best is kept.
+#[derive(Clone)]
+pub struct Alternatives {
+    /// The maximum number of plans to keep.
+    k: usize,
+
+    /// The plans.
+    plans: Vec<SolutionVertex>,
+}
+
+impl Alternatives {
+    /// Returns an empty set of alternatives holding at most `k` plans.
+    pub fn new(k: usize) -> Alternatives {
+        Alternatives {
+            k,
+            plans: Vec::new(),
+        }
+    }
+
+    /// Returns whether a plan with the given objective value would be kept.
+    pub fn accepts(&self, value: f32) -> bool {
+        self.plans.len() < self.k
+            || self
+                .plans
+                .last()
+                .is_some_and(|plan| value >= plan.value)
+    }
+
+    /// Adds a plan, keeping only the best `k` distinct plans.
+    ///
+    /// Ties are resolved in favor of the most recently added plan.
+    pub fn insert(&mut self, plan: SolutionVertex) {
+        if !self.accepts(plan.value) {
+            return;
+        }
+        // Replace an existing plan with the same order and allocation.
+        let same = |p: &SolutionVertex| p.order == plan.order && p.abilities == plan.abilities;
+        if let Some(pos) = self.plans.iter().position(same) {
+            if self.plans[pos].value > plan.value {
+                return;
+            }
+            self.plans.remove(pos);
+        }
+        // Insert the plan in order.
+        let pos = self
+            .plans
+            .iter()
+            .position(|p| p.value <= plan.value)
+            .unwrap_or(self.plans.len());
+        self.plans.insert(pos, plan);
+        self.plans.truncate(self.k);
+    }
+
+    /// Adds every plan of another set of alternatives.
+    pub fn extend(&mut self, other: &Alternatives) {
+        for plan in other.plans.iter() {
+            self.insert(plan.clone());
+        }
+    }
+
+    /// Returns the best plan.
+    pub fn best(&self) -> Option<&SolutionVertex> {
+        self.plans.first()
+    }
+
+    /// Returns the plans, sorted by decreasing objective value.
+    pub fn into_plans(self) -> Vec<SolutionVertex> {
+        self.plans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::abilities::{Abilities, Ability};
+
+    use super::*;
+
+    fn plan(log_proba: f32, order: Vec<usize>, diction: u8) -> SolutionVertex {
+        let mut abilities = Abilities::new();
+        abilities[Ability::Diction] = diction;
+        SolutionVertex {
+            cost: 0,
+            log_proba,
+            value: log_proba,
+            order,
+            abilities: vec![abilities, Abilities::new()],
+        }
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut alternatives = Alternatives::new(3);
+        alternatives.insert(plan(-1.0, vec![0, 1], 1));
+        alternatives.insert(plan(-0.5, vec![0, 1], 2));
+        // Same order and allocation as the previous plan.
+        alternatives.insert(plan(-0.4, vec![0, 1], 2));
+        alternatives.insert(plan(-2.0, vec![1, 0], 3));
+        // Same allocation as the previous plan, in another order.
+        alternatives.insert(plan(-0.6, vec![1, 0], 2));
+        alternatives.insert(plan(-0.8, vec![1, 0], 1));
+
+        let plans = alternatives.into_plans();
+        assert_eq!(plans.len(), 3);
+        assert_eq!(plans[0].log_proba, -0.4);
+        assert_eq!(plans[1].log_proba, -0.6);
+        assert_eq!(plans[2].log_proba, -0.8);
+    }
+}
@@ -0,0 +1,155 @@
+use std::path::PathBuf;
+
+use crate::{challenges::Challenge, constraints::Constraint, state::GameState};
+
+use super::{cache, Objective, SearchTables, SolutionVertex, SolverConfig};
+
+/// Solves boards, sharing the precomputed challenge setups between them.
+///
+/// Building a solver loads or computes the challenge setups, which is the expensive part of
+/// solving. A single solver should be reused for every board with the same ruleset.
+pub struct Solver {
+    tables: SearchTables,
+    config: SolverConfig,
+}
+
+/// Where a solver gets its challenge setups from.
+enum Source {
+    /// Load them from a cache file, or the default cache file if `None`.
+    Cache(Option<PathBuf>),
+
+    /// Compute them without touching the cache.
+    Compute,
+
+    /// Use tables that were already loaded.
+    Tables(SearchTables),
+}
+
+/// Configures and builds a `Solver`.
+pub struct SolverBuilder {
+    source: Source,
+    config: SolverConfig,
+}
+
+impl SolverBuilder {
+    /// Loads the challenge setups from the cache file at `path`, rebuilding it if needed.
+    pub fn cache_path<P: Into<PathBuf>>(mut self, path: P) -> SolverBuilder {
+        self.source = Source::Cache(Some(path.into()));
+        self
+    }
+
+    /// Computes the challenge setups without reading or writing the cache.
+    pub fn no_cache(mut self) -> SolverBuilder {
+        self.source = Source::Compute;
+        self
+    }
+
+    /// Uses challenge setups that were already loaded.
+    ///
+    /// The tables must have been computed with the same cost cap, ability cap and precision as the
+    /// config of the solver.
+    pub fn tables(mut self, tables: SearchTables) -> SolverBuilder {
+        self.source = Source::Tables(tables);
+        self
+    }
+
+    /// Ignores individual challenge setups with a success probability of at most `min_proba`.
+    pub fn min_proba(mut self, min_proba: f32) -> SolverBuilder {
+        self.config.min_proba = min_proba;
+        self
+    }
+
+    /// Replaces the limits and thresholds of the solver.
+    pub fn config(mut self, config: SolverConfig) -> SolverBuilder {
+        self.config = config;
+        self
+    }
+
+    /// Loads the challenge setups and returns the solver.
+    ///
+    /// Returns an error if the config is invalid, see `SolverConfig::validate`, or if the tables
+    /// were computed with another config.
+    pub fn build(self) -> Result<Solver, String> {
+        let config = self.config;
+        config.validate()?;
+        let tables = match self.source {
+            Source::Cache(path) => {
+                let path = path.unwrap_or_else(|| cache::default_path(&config));
+                cache::load_or_compute(path, &config)
+            }
+            Source::Compute => {
+                eprintln!("Generating individual challenge setups");
+                SearchTables::compute(&config)
+            }
+            Source::Tables(tables) => {
+                if !tables.config.same_tables(&config) {
+                    return Err("the search tables use another config".to_string());
+                }
+                tables
+            }
+        };
+        Ok(Solver { tables, config })
+    }
+}
+
+impl Solver {
+    /// Returns a builder that loads the challenge setups from the default cache file.
+    pub fn builder() -> SolverBuilder {
+        SolverBuilder {
+            source: Source::Cache(None),
+            config: SolverConfig::default(),
+        }
+    }
+
+    /// Returns the precomputed challenge setups.
+    pub fn tables(&self) -> &SearchTables {
+        &self.tables
+    }
+
+    /// Returns the limits and thresholds of the solver.
+    pub fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    /// Returns the best plan at every budget, sorted by increasing cost and probability.
+    pub fn solve(&self, challenges: &[Challenge]) -> Vec<SolutionVertex> {
+        super::solve(challenges, &self.tables, &self.config)
+    }
+
+    /// Returns the best plan at every budget for the challenges left in a game in progress,
+    /// subject to order constraints.
+    pub fn solve_from(
+        &self,
+        challenges: &[Challenge],
+        state: &GameState,
+        constraints: &[Constraint],
+    ) -> Vec<SolutionVertex> {
+        super::solve_from(challenges, &self.tables, state, constraints, &self.config)
+    }
+
+    /// Returns the `k` best distinct plans at every budget for the challenges left in a game in
+    /// progress, subject to order constraints.
+    pub fn solve_top_k(
+        &self,
+        challenges: &[Challenge],
+        state: &GameState,
+        constraints: &[Constraint],
+        k: usize,
+    ) -> Vec<Vec<SolutionVertex>> {
+        super::solve_top_k(challenges, &self.tables, state, constraints, k, &self.config)
+    }
+
+    /// Returns the `k` best distinct plans at every budget for an objective, sorted by increasing
+    /// cost and objective value.
+    pub fn solve_objective(
+        &self,
+        challenges: &[Challenge],
+        state: &GameState,
+        constraints: &[Constraint],
+        k: usize,
+        objective: &Objective,
+    ) -> Vec<Vec<SolutionVertex>> {
+        let (tables, config) = (&self.tables, &self.config);
+        super::solve_objective(challenges, tables, state, constraints, k, config, objective)
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    fs::{self, File},
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::{
+    abilities::{Abilities, Ability},
+    challenges::TARGET_SET,
+    distributions::Distribution,
+    rewards::Rewards,
+};
+
+use super::{
+    challenge::{empty_table, SearchEntry, SearchTables},
+    SolverConfig,
+};
+
+/// Identifies a search table cache file.
+const MAGIC: &[u8; 4] = b"BTVC";
+
+/// The version of the cache file format. Bump this whenever the layout changes.
+const VERSION: u32 = 1;
+
+/// The number of bytes used to store a single search entry.
+const ENTRY_SIZE: usize = 4 + 8;
+
+/// Returns a hash of the ruleset and config used to compute the search tables.
+///
+/// The hash covers the table dimensions, the precision, the targets and the distribution of a single
+/// roll of every ability under every reward combination, so any change to the dice or rewards
+/// invalidates the cache.
+pub fn ruleset_hash(config: &SolverConfig) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write_u64(config.costs() as u64);
+    hasher.write_u64(config.max_abilities as u64);
+    hasher.write_u64(config.precision as u64);
+    for target in TARGET_SET.iter() {
+        hasher.write_u64(*target as u64);
+    }
+    let max_target = TARGET_SET[TARGET_SET.len() - 1];
+    for rewards in Rewards::combinations() {
+        for ability in Ability::values() {
+            let distribution = Distribution::new().add_ability(ability, rewards);
+            for target in 0..=max_target {
+                hasher.write(&distribution.at_least(target).to_le_bytes());
+            }
+        }
+    }
+    hasher.finish()
+}
+
+/// Returns the default location of the cache file for the current ruleset and config.
+pub fn default_path(config: &SolverConfig) -> PathBuf {
+    std::env::temp_dir().join(format!("btv-{:016x}.cache", ruleset_hash(config)))
+}
+
+/// Loads the search tables from the cache file, rebuilding the cache if it is missing or stale.
+pub fn load_or_compute<P: AsRef<Path>>(path: P, config: &SolverConfig) -> SearchTables {
+    let path = path.as_ref();
+    let hash = ruleset_hash(config);
+    if let Ok(Some(tables)) = load(path, hash, config) {
+        return tables;
+    }
+    eprintln!("Generating individual challenge setups");
+    let tables = SearchTables::compute(config);
+    if let Err(err) = save(path, hash, &tables) {
+        eprintln!("Failed to write cache {}: {}", path.display(), err);
+    }
+    tables
+}
+
+/// Reads the search tables from a cache file.
+///
+/// Returns `None` if the file was written by another format version, ruleset or config, or is
+/// truncated.
+fn load(path: &Path, hash: u64, config: &SolverConfig) -> io::Result<Option<SearchTables>> {
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+    let mut reader = ByteReader { bytes: &bytes };
+
+    // Check the header.
+    if reader.take(MAGIC.len()) != Some(&MAGIC[..])
+        || reader.u32() != Some(VERSION)
+        || reader.u64() != Some(hash)
+    {
+        return Ok(None);
+    }
+
+    // Read the tables.
+    let count = match reader.u32() {
+        Some(count) => count,
+        None => return Ok(None),
+    };
+    let mut tables = HashMap::with_capacity(count as usize);
+    for _ in 0..count {
+        let rewards = match reader.u32().and_then(Rewards::from_bits) {
+            Some(rewards) => rewards,
+            None => return Ok(None),
+        };
+        let mut table = empty_table(config);
+        for entry in table.iter_mut().flatten().flatten() {
+            match reader.entry() {
+                Some(value) => *entry = value,
+                None => return Ok(None),
+            }
+        }
+        tables.insert(rewards, table);
+    }
+
+    if !reader.bytes.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(SearchTables {
+        config: *config,
+        tables,
+    }))
+}
+
+/// Writes the search tables to a cache file.
+///
+/// The tables are written to a temporary file first, so an interrupted write never leaves a
+/// truncated cache behind. The temporary file is named after the process, so that processes
+/// writing the same cache at once do not interleave their writes.
+fn save(path: &Path, hash: u64, tables: &SearchTables) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension(format!("{}.tmp", process::id()));
+    {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&hash.to_le_bytes())?;
+        writer.write_all(&(tables.tables.len() as u32).to_le_bytes())?;
+        for (rewards, table) in tables.tables.iter() {
+            writer.write_all(&rewards.bits().to_le_bytes())?;
+            for entry in table.iter().flatten().flatten() {
+                writer.write_all(&entry.proba.to_le_bytes())?;
+                for ability in Ability::values() {
+                    writer.write_all(&[entry.abilities[ability]])?;
+                }
+            }
+        }
+        writer.flush()?;
+    }
+    fs::rename(tmp_path, path)
+}
+
+/// A cursor over little-endian encoded bytes.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < len {
+            return None;
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Some(head)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn entry(&mut self) -> Option<SearchEntry> {
+        let bytes = self.take(ENTRY_SIZE)?;
+        let proba = f32::from_le_bytes(bytes[..4].try_into().ok()?);
+        let mut abilities = Abilities::new();
+        for (ability, count) in Ability::values().iter().zip(&bytes[4..]) {
+            abilities[*ability] = *count;
+        }
+        Some(SearchEntry { proba, abilities })
+    }
+}
+
+/// The 64 bit FNV-1a hash, which is stable across platforms and compiler versions.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_rejects_stale_cache() {
+        let path = std::env::temp_dir().join(format!("btv-test-{}.cache", std::process::id()));
+        let config = SolverConfig::default();
+        let mut tables = HashMap::new();
+        let mut table = empty_table(&config);
+        table[3][2][1] = SearchEntry {
+            proba: 0.5,
+            abilities: Abilities::new(),
+        };
+        tables.insert(Rewards::DICTION_RANGE, table);
+        save(&path, 42, &SearchTables { config, tables }).unwrap();
+
+        let loaded = load(&path, 42, &config).unwrap().unwrap();
+        assert_eq!(loaded.tables[&Rewards::DICTION_RANGE][3][2][1].proba, 0.5);
+        assert!(load(&path, 43, &config).unwrap().is_none());
+
+        // A config with other table dimensions reads past the end of the file.
+        let larger = SolverConfig {
+            max_abilities: 20,
+            ..config
+        };
+        assert!(load(&path, 42, &larger).unwrap().is_none());
+
+        fs::remove_file(path).unwrap();
+    }
+}
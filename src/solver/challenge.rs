@@ -1,12 +1,14 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, mem};
 
 use crate::{
     abilities::{Abilities, Ability},
-    challenges::{Challenge, MAX_ABILITIES, TARGET_SET},
+    challenges::{Challenge, TARGET_SET},
     distributions::Distribution,
     rewards::Rewards,
 };
 
+use super::{Objective, SolverConfig};
+
 /// Calculates and caches the optimal ability configurations of a set of challenges.
 pub struct ChallengeSolver {
     cache: HashMap<Rewards, Vec<ChallengeConvexHull>>,
@@ -14,10 +16,40 @@ pub struct ChallengeSolver {
 
 impl ChallengeSolver {
     /// Returns a challenge solver given a set of challenges to consider.
-    pub fn new(challenges: &Vec<Challenge>) -> ChallengeSolver {
+    ///
+    /// The convex hulls are built from precomputed search tables, which do not depend on the
+    /// challenges and can be shared between boards. The tables must have been computed with the
+    /// same cost cap, ability cap and precision as `config`. The hulls are taken over the
+    /// contribution of each challenge to the objective.
+    pub fn new(
+        challenges: &[Challenge],
+        tables: &SearchTables,
+        config: &SolverConfig,
+        objective: &Objective,
+    ) -> ChallengeSolver {
+        ChallengeSolver::with_owned(challenges, tables, config, objective, None)
+    }
+
+    /// Returns a challenge solver whose hulls are priced with a discount on owned dice.
+    ///
+    /// With `owned`, each challenge pays `share` of the price of the owned dice it uses. The owned
+    /// dice are shared by every challenge, so the discounted hulls are only a guide to plans that
+    /// rely on them, and the plans must still be checked against the state.
+    pub fn with_owned(
+        challenges: &[Challenge],
+        tables: &SearchTables,
+        config: &SolverConfig,
+        objective: &Objective,
+        owned: Option<(&Abilities, f32)>,
+    ) -> ChallengeSolver {
+        assert!(tables.config.same_tables(config), "search tables use another config");
         let mut cache = HashMap::new();
-        for rewards in Rewards::combinations() {
-            solve(rewards, challenges, &mut cache);
+        for (rewards, table) in tables.tables.iter() {
+            // Each table serves the rewards both with and without the additional ability reward.
+            for key in [*rewards, *rewards | Rewards::ADDITIONAL_ABILITY].iter() {
+                let hulls = hulls(table, *key, challenges, config.min_proba, objective, owned);
+                cache.insert(*key, hulls);
+            }
         }
         ChallengeSolver { cache }
     }
@@ -32,86 +64,270 @@ impl ChallengeSolver {
     }
 }
 
-/// Calculates the convex hull over optimal ability configurations given a set of rewards.
+/// The optimal ability setups of every reward combination.
 ///
-/// The convex hull is written to the output map. Both the given reward setup with and without the
-/// additional ability rewards are computed simultaneously.
-fn solve(
-    rewards: Rewards,
-    challenges: &Vec<Challenge>,
-    output: &mut HashMap<Rewards, Vec<ChallengeConvexHull>>,
-) {
-    // Bail early if this rewards has already been calculated.
-    if output.contains_key(&rewards) {
-        return;
+/// The tables depend only on the ruleset and the cost cap, ability cap and precision of the
+/// config, so they are computed once and may be cached to disk.
+pub struct SearchTables {
+    pub(super) config: SolverConfig,
+    pub(super) tables: HashMap<Rewards, SearchTable>,
+}
+
+impl SearchTables {
+    /// Computes the search tables of every reward combination.
+    pub fn compute(config: &SolverConfig) -> SearchTables {
+        let mut tables = HashMap::new();
+        for rewards in Rewards::combinations() {
+            // The additional ability reward is handled in the convex hull calculations.
+            if rewards.contains(Rewards::ADDITIONAL_ABILITY) {
+                continue;
+            }
+            tables.insert(rewards, search_table(rewards, config));
+        }
+        SearchTables {
+            config: *config,
+            tables,
+        }
     }
 
-    // Compute the optimal ability setup for each target given a fixed cost and abilities.
-    let mut solutions = empty_table();
+    /// Returns the config the tables were computed with.
+    pub fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+}
+
+/// Computes the optimal ability setup for each target given a fixed cost and max abilities.
+pub(super) fn search_table(rewards: Rewards, config: &SolverConfig) -> SearchTable {
+    let mut solutions = empty_table(config);
     search(
-        // Ignore the additional ability reward. It will be handled in the convex hull calculations.
-        rewards & !Rewards::ADDITIONAL_ABILITY,
+        rewards,
+        config,
         0,
         0,
         0,
         Abilities::new(),
-        &Distribution::new(),
+        &Distribution::with_precision(config.precision),
         &mut solutions,
     );
 
     // Ensure that each entry contains the optimal setup given a fixed cost and max abilities.
     ability_cummax(&mut solutions);
 
-    // Handle both with and with the addition ability reward simultaneously.
-    for key in [rewards, rewards ^ Rewards::ADDITIONAL_ABILITY].iter() {
-        let mut cache_value = Vec::with_capacity(challenges.len());
-        let mut probabilities = [Default::default(); COSTS];
-
-        for challenge in challenges.iter() {
-            // Gather the entries corresponding to the challenge.
-            for cost in 0..probabilities.len() {
-                let target = challenge.target_idx as usize;
-                let abilities = challenge.abilities as usize
-                    + (key.contains(Rewards::ADDITIONAL_ABILITY) as usize);
-                probabilities[cost] = solutions[cost][abilities][target];
+    solutions
+}
+
+/// Calculates the convex hulls over optimal ability configurations given a set of rewards.
+///
+/// The search table must have been computed with the same rewards, ignoring the additional
+/// ability reward. With `owned`, the setups are priced with a discount on the owned dice.
+fn hulls(
+    table: &SearchTable,
+    rewards: Rewards,
+    challenges: &[Challenge],
+    min_proba: f32,
+    objective: &Objective,
+    owned: Option<(&Abilities, f32)>,
+) -> Vec<ChallengeConvexHull> {
+    (0..challenges.len())
+        .map(|idx| hull(table, rewards, challenges, idx, min_proba, objective, owned))
+        .collect()
+}
+
+/// The optimal setups of challenges that use some of the owned dice for free, searched on demand.
+///
+/// Each search starts from the owned dice that are used, and adds bought dice up to the cost and
+/// ability caps. Its entries are indexed by the cost of the bought dice.
+pub(super) struct OwnedSearches<'a> {
+    tables: &'a SearchTables,
+    config: &'a SolverConfig,
+    objective: &'a Objective,
+    searches: HashMap<(Rewards, usize, Abilities), SearchTable>,
+}
+
+impl<'a> OwnedSearches<'a> {
+    /// Returns an empty set of searches, falling back to the precomputed tables when no owned dice
+    /// are used.
+    pub(super) fn new(
+        tables: &'a SearchTables,
+        config: &'a SolverConfig,
+        objective: &'a Objective,
+    ) -> OwnedSearches<'a> {
+        assert!(tables.config.same_tables(config), "search tables use another config");
+        OwnedSearches {
+            tables,
+            config,
+            objective,
+            searches: HashMap::new(),
+        }
+    }
+
+    /// Returns the convex hull of a challenge that may use the owned dice `part` for free, by the
+    /// cost of the bought dice.
+    pub(super) fn hull(
+        &mut self,
+        rewards: Rewards,
+        challenges: &[Challenge],
+        challenge_idx: usize,
+        part: &Abilities,
+    ) -> ChallengeConvexHull {
+        let config = self.config;
+        let challenge = &challenges[challenge_idx];
+        let abilities = (challenge.abilities as usize
+            + (rewards.contains(Rewards::ADDITIONAL_ABILITY) as usize))
+            .min(config.max_abilities);
+        let rewards = rewards & !Rewards::ADDITIONAL_ABILITY;
+        // The best setup at each cost, over the owned dice it uses.
+        let mut points: Vec<(u32, SearchEntry)> =
+            (0..config.costs()).map(|cost| (2 * cost as u32, SearchEntry::default())).collect();
+        for used in sub_parts(part).into_iter().filter(|used| count(used) <= abilities) {
+            // Without owned dice, the setups are in the precomputed tables.
+            let table = match used == Abilities::new() {
+                true => &self.tables.tables[&rewards],
+                false => self
+                    .searches
+                    .entry((rewards, abilities, used))
+                    .or_insert_with(|| search_from(rewards, config, abilities, used)),
+            };
+            for ((_, best), entries) in points.iter_mut().zip(table.iter()) {
+                let entry = entries[abilities][challenge.target_idx];
+                if entry.proba > best.proba {
+                    *best = entry;
+                }
             }
-            // Compute the convex hull over the entries.
-            cache_value.push(convex_hull(&probabilities));
         }
+        let weight = match self.objective {
+            Objective::PassAll => None,
+            Objective::Expected(values) => Some(values[challenge_idx]),
+        };
+        convex_hull(&points, config.min_proba, weight)
+    }
+}
 
-        output.insert(*key, cache_value);
+/// Computes the optimal setups that use the dice `used` for free plus bought dice, by the cost of
+/// the bought dice, up to `max_abilities` abilities.
+fn search_from(
+    rewards: Rewards,
+    config: &SolverConfig,
+    max_abilities: usize,
+    used: Abilities,
+) -> SearchTable {
+    let config = SolverConfig {
+        max_abilities,
+        ..*config
+    };
+    let mut distribution = Distribution::with_precision(config.precision);
+    for ability in Ability::values() {
+        for _ in 0..used[ability] {
+            distribution = distribution.add_ability(ability, rewards);
+        }
     }
+    let mut solutions = empty_table(&config);
+    search(rewards, &config, 0, 0, count(&used), used, &distribution, &mut solutions);
+    ability_cummax(&mut solutions);
+    solutions
 }
 
-/// The maximum cost to consider of a single single challenge.
-const MAX_COST: usize = 300;
+/// Returns every set of dice that is part of `part`.
+fn sub_parts(part: &Abilities) -> Vec<Abilities> {
+    let mut output = vec![Abilities::new()];
+    for ability in Ability::values() {
+        output = output
+            .into_iter()
+            .flat_map(|abilities| {
+                (0..=part[ability]).map(move |n| {
+                    let mut abilities = abilities;
+                    abilities[ability] = n;
+                    abilities
+                })
+            })
+            .collect();
+    }
+    output
+}
+
+/// Returns the number of dice.
+fn count(abilities: &Abilities) -> usize {
+    Ability::values().iter().map(|ability| abilities[*ability] as usize).sum()
+}
+
+/// Calculates the convex hull over the optimal ability configurations of a challenge.
+fn hull(
+    table: &SearchTable,
+    rewards: Rewards,
+    challenges: &[Challenge],
+    idx: usize,
+    min_proba: f32,
+    objective: &Objective,
+    owned: Option<(&Abilities, f32)>,
+) -> ChallengeConvexHull {
+    let challenge = &challenges[idx];
+    // Gather the entries corresponding to the challenge. Abilities beyond the cap are unused.
+    let abilities = (challenge.abilities as usize
+        + (rewards.contains(Rewards::ADDITIONAL_ABILITY) as usize))
+        .min(table[0].len() - 1);
+    let points: Vec<(u32, SearchEntry)> = match owned {
+        None => table
+            .iter()
+            .enumerate()
+            .map(|(cost, entries)| (2 * cost as u32, entries[abilities][challenge.target_idx]))
+            .collect(),
+        // The best setup at a discounted cost may use fewer abilities than the cap.
+        Some((dice, share)) => {
+            let mut points: Vec<(u32, SearchEntry)> = table
+                .iter()
+                .flat_map(|entries| entries[..=abilities].iter())
+                .map(|targets| targets[challenge.target_idx])
+                .map(|entry| (discounted_cost(&entry.abilities, dice, share), entry))
+                .collect();
+            points.sort_by(|(a_cost, a), (b_cost, b)| {
+                a_cost.cmp(b_cost).then(b.proba.partial_cmp(&a.proba).unwrap())
+            });
+            points.dedup_by_key(|(cost, _)| *cost);
+            points
+        }
+    };
+    // Compute the convex hull over the entries.
+    let weight = match objective {
+        Objective::PassAll => None,
+        Objective::Expected(values) => Some(values[idx]),
+    };
+    convex_hull(&points, min_proba, weight)
+}
 
-/// The number of different cost values to consider. The cost of any ability combination must be even.
-const COSTS: usize = MAX_COST / 2 + 1;
+/// Returns the cost of a setup that pays `share` of the price of the owned dice it uses.
+fn discounted_cost(abilities: &Abilities, owned: &Abilities, share: f32) -> u32 {
+    let used: u32 = Ability::values()
+        .iter()
+        .map(|ability| abilities[*ability].min(owned[*ability]) as u32 * ability.cost())
+        .sum();
+    abilities.cost() - (used as f32 * (1.0 - share)).round() as u32
+}
 
 /// An entry in the search table.
 #[derive(Copy, Clone, Debug, Default)]
-struct SearchEntry {
+pub(super) struct SearchEntry {
     /// The success probabilty of the ability configuration.
-    proba: f32,
+    pub(super) proba: f32,
 
     /// The ability configuration.
-    abilities: Abilities,
+    pub(super) abilities: Abilities,
 }
 
-/// A mapping from (cost, abilties_used, target) triples to ability configurations.
-type SearchTable = [[[SearchEntry; TARGET_SET.len()]; MAX_ABILITIES + 1]; COSTS];
+/// A mapping from (cost / 2, abilties_used, target) triples to ability configurations.
+pub(super) type SearchTable = Vec<Vec<[SearchEntry; TARGET_SET.len()]>>;
 
-/// Returns an empty search table.
-fn empty_table() -> SearchTable {
-    [[[Default::default(); TARGET_SET.len()]; MAX_ABILITIES + 1]; COSTS]
+/// Returns an empty search table sized by the cost and ability caps of the config.
+pub(super) fn empty_table(config: &SolverConfig) -> SearchTable {
+    vec![vec![[SearchEntry::default(); TARGET_SET.len()]; config.max_abilities + 1]; config.costs()]
 }
 
 /// Search for optimal ability setups of (cost, abilities used, target) triples.
 ///
 /// Each entry will match the cost and abilities used exactly. Results are written to `solutions`.
+#[allow(clippy::too_many_arguments)]
 fn search(
     rewards: Rewards,
+    config: &SolverConfig,
     ability_idx: usize,
     cost: usize,
     total_abilities: usize,
@@ -119,7 +335,7 @@ fn search(
     distribution: &Distribution,
     table: &mut SearchTable,
 ) {
-    if cost > MAX_COST {
+    if cost > config.max_cost as usize {
         return;
     }
 
@@ -133,16 +349,18 @@ fn search(
         }
     }
 
-    if ability_idx >= Ability::values().len() || total_abilities >= MAX_ABILITIES {
+    if ability_idx >= Ability::values().len() || total_abilities >= config.max_abilities {
         return;
     }
 
     let ability = Ability::values()[ability_idx];
     let mut new_distribution = distribution.clone();
+    let mut next_distribution = distribution.clone();
 
-    for rolls in 0..(MAX_ABILITIES - total_abilities + 1) {
+    for rolls in 0..(config.max_abilities - total_abilities + 1) {
         search(
             rewards,
+            config,
             ability_idx + 1,
             cost + rolls * (ability.cost() as usize),
             total_abilities + rolls,
@@ -150,7 +368,8 @@ fn search(
             &new_distribution,
             table,
         );
-        new_distribution = new_distribution.add_ability(ability, rewards);
+        new_distribution.add_ability_to(ability, rewards, &mut next_distribution);
+        mem::swap(&mut new_distribution, &mut next_distribution);
         abilities[ability] += 1;
     }
 }
@@ -158,11 +377,11 @@ fn search(
 /// Computes the cummulative maximum over the ability axis.
 fn ability_cummax(table: &mut SearchTable) {
     // Sweep over abilities used
-    for cost in 0..COSTS {
+    for entries in table.iter_mut() {
         for target in 0..TARGET_SET.len() {
-            let mut best = table[cost][0][target];
-            for abilities in 1..MAX_ABILITIES + 1 {
-                let value = &mut table[cost][abilities][target];
+            let mut best = entries[0][target];
+            for targets in entries.iter_mut().skip(1) {
+                let value = &mut targets[target];
                 if best.proba > value.proba {
                     *value = best;
                 } else {
@@ -182,7 +401,10 @@ pub struct ChallengeVertex {
     /// The log probability of passing the challenge.
     pub log_proba: f32,
 
-    /// The (log probability / cost) slope with respect to the previous vertex on the convex hull.
+    /// The contribution of the challenge to the objective.
+    pub value: f32,
+
+    /// The (value / cost) slope with respect to the previous vertex on the convex hull.
     pub slope: f32,
 
     /// The ability configuration.
@@ -192,24 +414,31 @@ pub struct ChallengeVertex {
 /// A convex hull over challenge solutions.
 pub type ChallengeConvexHull = Vec<ChallengeVertex>;
 
-const MIN_PROBA: f32 = 0.1;
-
-/// Returns the convex hull over an array of challenge solutions.
-fn convex_hull<T: AsRef<[SearchEntry]>>(curve: T) -> ChallengeConvexHull {
+/// Returns the convex hull over challenge solutions, sorted by increasing cost.
+///
+/// Without a weight, the hull is taken over the log probability and solutions with a success
+/// probability of at most `min_proba` are ignored. With a weight, the hull is taken over the
+/// probability times the weight, and every solution is considered.
+fn convex_hull(
+    curve: &[(u32, SearchEntry)],
+    min_proba: f32,
+    weight: Option<f32>,
+) -> ChallengeConvexHull {
     let mut hull: ChallengeConvexHull = vec![];
 
-    for (idx, solution) in curve.as_ref().iter().enumerate() {
+    for (cost, solution) in curve.iter() {
         // Ignore values within epsilon of 0
-        if solution.proba <= MIN_PROBA {
+        if weight.is_none() && solution.proba <= min_proba {
             continue;
         }
 
-        let cost = 2 * idx;
+        let cost = *cost;
         let log_proba = solution.proba.ln();
+        let value = weight.map_or(log_proba, |weight| weight * solution.proba);
 
         let mut slope = f32::MAX;
         while let Some(prev) = hull.last() {
-            slope = (prev.log_proba - log_proba) / ((prev.cost as f32) - (cost as f32));
+            slope = (prev.value - value) / ((prev.cost as f32) - (cost as f32));
             if slope < prev.slope {
                 break;
             }
@@ -217,8 +446,9 @@ fn convex_hull<T: AsRef<[SearchEntry]>>(curve: T) -> ChallengeConvexHull {
         }
 
         hull.push(ChallengeVertex {
-            cost: cost as u32,
+            cost,
             log_proba,
+            value,
             slope,
             abilities: solution.abilities,
         });
@@ -240,10 +470,11 @@ mod tests {
 
     const EPSILON: f32 = 1e-6;
 
-    fn solve(challenges: &Vec<Challenge>, rewards: Rewards, idx: usize) -> ChallengeConvexHull {
-        let mut output = HashMap::new();
-        super::solve(rewards, challenges, &mut output);
-        output.remove(&rewards).unwrap().swap_remove(idx)
+    fn solve(challenges: &[Challenge], rewards: Rewards, idx: usize) -> ChallengeConvexHull {
+        let config = SolverConfig::default();
+        let table = search_table(rewards & !Rewards::ADDITIONAL_ABILITY, &config);
+        let objective = Objective::PassAll;
+        hulls(&table, rewards, challenges, config.min_proba, &objective, None).swap_remove(idx)
     }
 
     #[test]
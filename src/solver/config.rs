@@ -0,0 +1,122 @@
+use crate::{abilities::Ability, challenges::TARGET_SET, distributions::DEFAULT_PRECISION};
+
+/// The largest cost cap of a single challenge, so that the search tables fit in memory.
+const MAX_COST: u32 = 10000;
+
+/// The largest cost cap of all challenges, so that the frontier fits in memory.
+const MAX_TOTAL_COST: u32 = 100000;
+
+/// The limits and thresholds of the solver.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SolverConfig {
+    /// The maximum cost to spend on a single challenge.
+    pub max_cost: u32,
+
+    /// The maximum cost to spend on all challenges.
+    pub max_total_cost: u32,
+
+    /// The maximum number of abilities on a single challenge, including the additional ability
+    /// reward.
+    pub max_abilities: usize,
+
+    /// Challenge setups with a success probability of at most this value are ignored.
+    pub min_proba: f32,
+
+    /// The number of roll totals tracked by the distributions. Must be larger than every target.
+    pub precision: usize,
+}
+
+/// The quantity maximized by the solver.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Objective {
+    /// The probability of passing every challenge.
+    PassAll,
+
+    /// The expected sum of the values of the challenges passed, indexed by challenge.
+    ///
+    /// The challenges are combined additively, assuming that each challenge is attempted once and
+    /// that the rewards of the challenges before it were earned.
+    Expected(Vec<f32>),
+}
+
+impl SolverConfig {
+    /// Returns an error describing the first option outside of the range the solver supports.
+    pub fn validate(&self) -> Result<(), String> {
+        let cheapest = Ability::values().iter().map(|ability| ability.cost()).min().unwrap();
+        if !(cheapest..=MAX_COST).contains(&self.max_cost) {
+            return Err(format!(
+                "max cost must be at least {} and at most {}",
+                cheapest, MAX_COST
+            ));
+        }
+        if !(self.max_cost..=MAX_TOTAL_COST).contains(&self.max_total_cost) {
+            return Err(format!(
+                "max total cost must be at least the max cost and at most {}",
+                MAX_TOTAL_COST
+            ));
+        }
+        let max_target = *TARGET_SET.iter().max().unwrap();
+        if self.precision <= max_target {
+            return Err(format!("precision must be larger than every target ({})", max_target));
+        }
+        if self.max_abilities > u8::MAX as usize {
+            return Err(format!("max abilities must be at most {}", u8::MAX));
+        }
+        if !(0.0..1.0).contains(&self.min_proba) {
+            return Err("min proba must be at least 0 and below 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the number of even costs up to the maximum cost of a single challenge.
+    pub(super) fn costs(&self) -> usize {
+        self.max_cost as usize / 2 + 1
+    }
+
+    /// Returns the number of even costs up to the maximum cost of all challenges.
+    pub(super) fn total_costs(&self) -> usize {
+        self.max_total_cost as usize / 2 + 1
+    }
+
+    /// Returns whether search tables computed with either config are the same.
+    pub(super) fn same_tables(&self, other: &SolverConfig) -> bool {
+        self.max_cost == other.max_cost
+            && self.max_abilities == other.max_abilities
+            && self.precision == other.precision
+    }
+}
+
+impl Default for SolverConfig {
+    fn default() -> SolverConfig {
+        SolverConfig {
+            max_cost: 300,
+            max_total_cost: 1200,
+            max_abilities: 16,
+            min_proba: 0.1,
+            precision: DEFAULT_PRECISION,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let config = SolverConfig::default();
+        assert!(config.validate().is_ok());
+        assert!(SolverConfig { precision: 70, ..config }.validate().is_err());
+        assert!(SolverConfig { precision: 0, ..config }.validate().is_err());
+        assert!(SolverConfig { max_abilities: 256, ..config }.validate().is_err());
+        assert!(SolverConfig { min_proba: 1.0, ..config }.validate().is_err());
+        assert!(SolverConfig { min_proba: -0.1, ..config }.validate().is_err());
+        assert!(SolverConfig { min_proba: f32::NAN, ..config }.validate().is_err());
+        assert!(SolverConfig { max_cost: 2, ..config }.validate().is_err());
+        assert!(SolverConfig { max_cost: 20000, ..config }.validate().is_err());
+        assert!(SolverConfig { max_total_cost: 200, ..config }.validate().is_err());
+        assert!(SolverConfig { max_total_cost: u32::MAX, ..config }.validate().is_err());
+        let small = SolverConfig { max_cost: 4, max_total_cost: 4, ..config };
+        assert!(small.validate().is_ok());
+    }
+}
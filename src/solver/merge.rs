@@ -2,30 +2,36 @@ use std::{cmp::Ordering, collections::BinaryHeap};
 
 use crate::abilities::Abilities;
 
-use super::challenge::ChallengeConvexHull;
+use super::{challenge::ChallengeConvexHull, SolverConfig};
 
 /// A vertex on the convex hull of the challenges solution.
 #[derive(Debug)]
 pub struct ChallengesVertex {
-    /// The total cost of the challenges.
-    pub cost: u32,
-
     /// The log probability of succeeding in all challenges.
     pub log_proba: f32,
 
+    /// The sum of the values of the challenges.
+    pub value: f32,
+
     /// The configuration of abilities for each challenge.
     pub configuration: Vec<Abilities>,
 }
 
 type ChallengesConvexHull = Vec<ChallengesVertex>;
 
-/// The maximum cost to consider spending on the challenges.
-pub const MAX_TOTAL_COST: u32 = 1200;
-
-pub fn merge_hulls<T: AsRef<ChallengeConvexHull> + Sized>(hulls: &Vec<T>) -> ChallengesConvexHull {
+/// Merges the convex hulls of challenges attempted in sequence, up to the total cost cap.
+///
+/// The values of the challenges are summed, so the merged hull is exact at its vertices for any
+/// additive objective.
+pub fn merge_hulls<T: AsRef<ChallengeConvexHull> + Sized>(
+    hulls: &[T],
+    config: &SolverConfig,
+) -> ChallengesConvexHull {
     // Initialize running variables
     let mut cost = 0;
     let mut log_proba = 0.0;
+    let mut value = 0.0;
+    let mut points = vec![0; hulls.len()];
     let mut configuration = {
         let mut configuration = Vec::new();
         configuration.reserve_exact(hulls.len());
@@ -41,29 +47,30 @@ pub fn merge_hulls<T: AsRef<ChallengeConvexHull> + Sized>(hulls: &Vec<T>) -> Cha
 
     // Iterate through each hull
     for (idx, hull) in hulls.iter().enumerate() {
-        match hull.as_ref().get(0) {
+        match hull.as_ref().first() {
             Some(point) => {
                 cost += point.cost;
                 log_proba += point.log_proba;
+                value += point.value;
                 configuration[idx] = point.abilities;
             }
             None => {
                 return vec![];
             }
         }
-        match hull.as_ref().get(1) {
-            Some(point) => {
-                let prev_cost = hull.as_ref()[0].cost;
-                heap.push((OrdF32(point.slope), point.cost - prev_cost, idx, 1));
-            }
-            None => {}
+        if let Some(point) = hull.as_ref().get(1) {
+            let prev_cost = hull.as_ref()[0].cost;
+            heap.push((OrdF32(point.slope), point.cost - prev_cost, idx, 1));
         }
     }
 
+    if cost > config.max_total_cost {
+        return vec![];
+    }
     let mut output = vec![];
     output.push(ChallengesVertex {
-        cost,
         log_proba,
+        value,
         configuration: configuration.clone(),
     });
 
@@ -71,10 +78,18 @@ pub fn merge_hulls<T: AsRef<ChallengeConvexHull> + Sized>(hulls: &Vec<T>) -> Cha
         // Update tracking variables.
         let h = hulls[hull].as_ref();
         cost += marginal_cost;
-        log_proba += h[idx].log_proba - h[idx - 1].log_proba;
-        configuration[hull as usize] = hulls[hull as usize].as_ref()[idx].abilities;
+        value += h[idx].value - h[idx - 1].value;
+        points[hull] = idx;
+        // Hulls of other objectives may start at a zero probability, so the log probability is
+        // summed anew after leaving such a point.
+        log_proba = if h[idx - 1].log_proba.is_finite() {
+            log_proba + (h[idx].log_proba - h[idx - 1].log_proba)
+        } else {
+            hulls.iter().zip(&points).map(|(hull, idx)| hull.as_ref()[*idx].log_proba).sum()
+        };
+        configuration[hull] = h[idx].abilities;
         // Check for early exit.
-        if cost > MAX_TOTAL_COST {
+        if cost > config.max_total_cost {
             break;
         }
         // Push the next point of the hull onto the heap
@@ -83,8 +98,8 @@ pub fn merge_hulls<T: AsRef<ChallengeConvexHull> + Sized>(hulls: &Vec<T>) -> Cha
         }
         // Add the vertex to the output.
         output.push(ChallengesVertex {
-            cost,
             log_proba,
+            value,
             configuration: configuration.clone(),
         });
     }
@@ -92,45 +107,66 @@ pub fn merge_hulls<T: AsRef<ChallengeConvexHull> + Sized>(hulls: &Vec<T>) -> Cha
     output
 }
 
-#[derive(PartialEq, PartialOrd)]
+#[derive(PartialEq)]
 struct OrdF32(f32);
 
 impl Eq for OrdF32 {}
 
+impl PartialOrd for OrdF32 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Ord for OrdF32 {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.0.partial_cmp(&other.0).unwrap()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::solver::challenge::ChallengeVertex;
+    use crate::{abilities::Ability, solver::challenge::ChallengeVertex};
 
     use super::*;
 
     #[test]
     fn test_merge_hulls() {
+        // Each hull vertex uses `dice` Atmosphere dice.
+        let vertex = |dice, log_proba, slope| {
+            let mut abilities = Abilities::new();
+            abilities[Ability::Atmosphere] = dice;
+            ChallengeVertex {
+                cost: abilities.cost(),
+                log_proba,
+                value: log_proba,
+                slope,
+                abilities,
+            }
+        };
         let hulls = vec![
             vec![
-                ChallengeVertex{cost: 5, log_proba: -1.0, slope: 0.0, abilities: Abilities::new()},
-                ChallengeVertex{cost: 6, log_proba: -0.5, slope: 0.5, abilities: Abilities::new()},
-                ChallengeVertex{cost: 10, log_proba: 0.0, slope: 0.125, abilities: Abilities::new()},
+                vertex(5, -1.0, 0.0),
+                vertex(6, -0.5, 0.125),
+                vertex(10, 0.0, 0.03125),
             ],
             vec![
-                ChallengeVertex{cost: 1, log_proba: -2.0, slope: 0.0, abilities: Abilities::new()},
-                ChallengeVertex{cost: 5, log_proba: -1.0, slope: 0.25, abilities: Abilities::new()},
+                vertex(1, -2.0, 0.0),
+                vertex(5, -1.0, 0.0625),
             ],
         ];
-        let merged_hull = merge_hulls(&hulls);
+        let merged_hull = merge_hulls(&hulls, &SolverConfig::default());
+        let dice = |vertex: &ChallengesVertex| {
+            vertex.configuration.iter().map(|abilities| abilities[Ability::Atmosphere]).sum::<u8>()
+        };
         assert_eq!(merged_hull.len(), 4);
-        assert_eq!(merged_hull[0].cost, 6);
+        assert_eq!(dice(&merged_hull[0]), 6);
         assert_eq!(merged_hull[0].log_proba, -3.0);
-        assert_eq!(merged_hull[1].cost, 7);
+        assert_eq!(dice(&merged_hull[1]), 7);
         assert_eq!(merged_hull[1].log_proba, -2.5);
-        assert_eq!(merged_hull[2].cost, 11);
+        assert_eq!(dice(&merged_hull[2]), 11);
         assert_eq!(merged_hull[2].log_proba, -1.5);
-        assert_eq!(merged_hull[3].cost, 15);
+        assert_eq!(dice(&merged_hull[3]), 15);
         assert_eq!(merged_hull[3].log_proba, -1.0);
     }
 }
\ No newline at end of file
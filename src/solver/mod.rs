@@ -1,82 +1,667 @@
+use std::{collections::HashMap, ops::Add};
+
 use itertools::Itertools;
 
-use crate::{abilities::Abilities, challenges::Challenge, rewards::Rewards};
+use crate::{
+    abilities::{Abilities, Ability},
+    challenges::Challenge,
+    constraints::Constraint,
+    rewards::Rewards,
+    state::GameState,
+};
+
+use self::{
+    alternatives::Alternatives,
+    challenge::{ChallengeConvexHull, ChallengeSolver, OwnedSearches},
+    merge::{merge_hulls, ChallengesVertex},
+};
 
-use self::{challenge::ChallengeSolver, merge::{MAX_TOTAL_COST, merge_hulls}};
+pub use self::{
+    builder::{Solver, SolverBuilder},
+    challenge::SearchTables,
+    config::{Objective, SolverConfig},
+};
 
+mod alternatives;
+mod builder;
+pub mod cache;
 mod challenge;
+mod config;
 mod merge;
+#[cfg(test)]
+mod reference;
+
+/// The numbers of challenges the owned dice are split between when pricing the hulls. Each
+/// challenge uses its part of the owned dice for free.
+const OWNED_SPLITS: [u8; 6] = [1, 2, 3, 4, 6, 8];
+
+/// The shares of the price of the owned dice that the hulls are also priced with, on top of the
+/// full price.
+const OWNED_SHARES: [f32; 3] = [0.5, 0.8, 0.9];
 
-/// The number of cost values to considers. The total cost will be even.
-const TOTAL_COSTS: usize = (MAX_TOTAL_COST as usize) / 2 + 1;
+/// The most hulls merged over every challenge order and split of the owned dice between the
+/// challenges. Beyond it, the owned dice are only split in the ways above.
+const MAX_OWNED_MERGES: u128 = 100000;
 
-#[derive(Debug)]
+/// A challenge order and ability setup on the solution frontier.
+#[derive(Clone, Debug)]
 pub struct SolutionVertex {
+    /// The points spent buying the abilities that are not owned. Without owned dice, this is the
+    /// total cost of the abilities.
     pub cost: u32,
+
+    /// The log probability of passing every challenge.
     pub log_proba: f32,
+
+    /// The objective value of the plan, which equals `log_proba` unless another objective was
+    /// maximized.
+    pub value: f32,
+
+    /// The indices of the challenges, in play order.
     pub order: Vec<usize>,
+
+    /// The abilities used on each challenge, in play order.
     pub abilities: Vec<Abilities>,
 }
 
-pub fn solve(challenges: &Vec<Challenge>) -> Vec<SolutionVertex> {
-    // Precompute challenge setups
-    println!("Generating individual challenge setups");
-    let challenge_solutions = ChallengeSolver::new(challenges);
-    // Separate challenges with and without rewards
-    let mut no_rewards = Vec::new();
-    let mut rewards = Vec::new();
-    for (idx, challenge) in challenges.iter().enumerate() {
-        match challenge.reward {
-            Rewards::NONE => no_rewards.push(idx),
-            _ => rewards.push(idx),
+/// Solves the challenges within the limits of the config.
+pub fn solve(
+    challenges: &[Challenge],
+    tables: &SearchTables,
+    config: &SolverConfig,
+) -> Vec<SolutionVertex> {
+    solve_from(challenges, tables, &GameState::new(), &[], config)
+}
+
+/// Solves the challenges that remain in a game in progress, subject to order constraints.
+///
+/// Only plans whose abilities can be assembled from the owned dice and the points left are kept.
+/// The cost of each plan is the points spent on top of the owned dice, which are free. The owned
+/// dice are split between the challenges in every way if there are few enough ways, see
+/// `splits_owned_dice_exactly`, and only in a few ways otherwise. Pinned positions count over the
+/// remaining challenges. Returns an empty frontier if the constraints cannot be satisfied.
+pub fn solve_from(
+    challenges: &[Challenge],
+    tables: &SearchTables,
+    state: &GameState,
+    constraints: &[Constraint],
+    config: &SolverConfig,
+) -> Vec<SolutionVertex> {
+    solve_top_k(challenges, tables, state, constraints, 1, config)
+        .into_iter()
+        .map(|mut plans| plans.swap_remove(0))
+        .collect()
+}
+
+/// Solves the remaining challenges, keeping the `k` best distinct plans at each budget.
+///
+/// Each entry of the frontier holds the best plans whose cost is at most the cost of the first
+/// plan, sorted by decreasing probability. Plans are distinct if they attempt the challenges in a
+/// different order or use different abilities on at least one challenge.
+pub fn solve_top_k(
+    challenges: &[Challenge],
+    tables: &SearchTables,
+    state: &GameState,
+    constraints: &[Constraint],
+    k: usize,
+    config: &SolverConfig,
+) -> Vec<Vec<SolutionVertex>> {
+    solve_objective(challenges, tables, state, constraints, k, config, &Objective::PassAll)
+}
+
+/// Solves the remaining challenges for an objective, keeping the `k` best distinct plans at each
+/// budget.
+///
+/// The frontier is sorted by increasing cost and objective value. Each entry holds the best plans
+/// whose cost is at most the cost of the first plan, sorted by decreasing objective value.
+pub fn solve_objective(
+    challenges: &[Challenge],
+    tables: &SearchTables,
+    state: &GameState,
+    constraints: &[Constraint],
+    k: usize,
+    config: &SolverConfig,
+    objective: &Objective,
+) -> Vec<Vec<SolutionVertex>> {
+    let Sequence {
+        slots,
+        rewards,
+        no_rewards,
+    } = match sequence(challenges, state, constraints) {
+        Some(sequence) => sequence,
+        None => return vec![],
+    };
+    // With owned dice, split them between the challenges in every way if there are few enough
+    // ways. Each challenge then uses its part of the owned dice for free.
+    let exact = state.dice != Abilities::new()
+        && few_owned_splits(&state.dice, slots.len(), rewards.len());
+    let owned_splits = if exact { owned_splits(&state.dice, slots.len()) } else { vec![] };
+    // Precompute challenge setups, unless the owned dice are split in every way. With owned dice,
+    // the setups are also priced with the owned dice at a discount, so that the merged hulls pass
+    // through plans relying on them.
+    let mut challenge_solvers = Vec::new();
+    if !exact {
+        challenge_solvers.push(ChallengeSolver::new(challenges, tables, config, objective));
+    }
+    if state.dice != Abilities::new() && !exact {
+        let mut pricings = Vec::new();
+        for split in OWNED_SPLITS.iter() {
+            let mut part = Abilities::new();
+            for ability in Ability::values().iter() {
+                part[*ability] = state.dice[*ability].div_ceil(*split);
+            }
+            pricings.push((part, 0.0));
+        }
+        pricings.extend(OWNED_SHARES.iter().map(|share| (state.dice, *share)));
+        pricings.dedup();
+        for (owned, share) in &pricings {
+            challenge_solvers.push(ChallengeSolver::with_owned(
+                challenges,
+                tables,
+                config,
+                objective,
+                Some((owned, *share)),
+            ));
         }
     }
+    let mut owned_searches = OwnedSearches::new(tables, config, objective);
+    let mut owned_hulls: HashMap<(Rewards, usize, Abilities), ChallengeConvexHull> = HashMap::new();
     // Generate solution table
-    const INIT: Option<SolutionVertex> = None;
-    let mut table = [INIT; TOTAL_COSTS];
+    let mut table = vec![Alternatives::new(k); config.total_costs()];
     // Loop over each permutation of challenges.
-    println!("Searching for optimal challenge order");
     let mut hulls = Vec::new();
     hulls.reserve_exact(challenges.len());
+    let initial_rewards = state.rewards(challenges);
     for head in rewards.iter().permutations(rewards.len()) {
-        let mut rewards = Rewards::NONE;
-        hulls.clear();
-        // Sequence the challenges with no rewards after the other challenges.
-        let permutation: Vec<usize> = head.into_iter().chain(no_rewards.iter()).cloned().collect();
-        // Collect the hulls.
-        for idx in &permutation {
-            hulls.push(challenge_solutions.solution_to(rewards, *idx));
-            rewards |= challenges[*idx].reward;
-        }
-        // Merge the hulls.
-        let merged_hull = merge_hulls(&hulls);
-        // Add hull to the table.
-        for candidate in merged_hull {
-            let idx = candidate.cost as usize / 2;
-            if let Some(vertex) = &table[idx] {
-                if vertex.log_proba > candidate.log_proba {
-                    continue;
-                }
+        // Sequence the challenges with no rewards after the other challenges, around the pinned
+        // challenges.
+        let mut free = head.into_iter().chain(no_rewards.iter()).cloned();
+        let permutation: Vec<usize> = slots
+            .iter()
+            .map(|slot| slot.or_else(|| free.next()).unwrap())
+            .collect();
+        if !constraints.iter().all(|c| c.allows(&permutation)) {
+            continue;
+        }
+        for challenge_solutions in &challenge_solvers {
+            // Collect the hulls.
+            let mut rewards = initial_rewards;
+            hulls.clear();
+            for idx in &permutation {
+                hulls.push(challenge_solutions.solution_to(rewards, *idx));
+                rewards |= challenges[*idx].reward;
             }
-            table[idx] = Some(SolutionVertex {
-                cost: candidate.cost,
-                log_proba: candidate.log_proba,
-                order: permutation.clone(),
-                abilities: candidate.configuration,
-            })
+            // Merge the hulls.
+            add_plans(&mut table, merge_hulls(&hulls, config), state, &permutation);
+        }
+        for split in &owned_splits {
+            // Price each challenge with its part of the owned dice for free.
+            let mut rewards = initial_rewards;
+            let mut keys = Vec::with_capacity(permutation.len());
+            for (idx, part) in permutation.iter().zip(split) {
+                let key = (rewards, *idx, *part);
+                owned_hulls
+                    .entry(key)
+                    .or_insert_with(|| owned_searches.hull(rewards, challenges, *idx, part));
+                keys.push(key);
+                rewards |= challenges[*idx].reward;
+            }
+            let hulls: Vec<&ChallengeConvexHull> =
+                keys.iter().map(|key| &owned_hulls[key]).collect();
+            add_plans(&mut table, merge_hulls(&hulls, config), state, &permutation);
         }
     }
     // Gather the vertices from the table
-    let mut output: Vec<SolutionVertex> = Vec::new();
+    let mut output: Vec<Vec<SolutionVertex>> = Vec::new();
+    let mut best = Alternatives::new(k);
+    let mut best_value = None;
     for entry in table {
-        match entry {
-            Some(vertex) => {
-                if output.last().map_or(true, |v| vertex.log_proba > v.log_proba) {
-                    output.push(vertex);
+        best.extend(&entry);
+        let value = entry.best().map(|v| v.value);
+        if value > best_value {
+            best_value = value;
+            output.push(best.clone().into_plans());
+        }
+    }
+    output
+}
+
+/// The challenges left to attempt, in the groups they are sequenced by.
+struct Sequence {
+    /// The challenge pinned to each position, if any.
+    slots: Vec<Option<usize>>,
+
+    /// The challenges whose order is searched.
+    rewards: Vec<usize>,
+
+    /// The challenges attempted after the others, in any order.
+    no_rewards: Vec<usize>,
+}
+
+/// Groups the challenges left to attempt, or returns `None` if the constraints cannot be
+/// satisfied.
+fn sequence(
+    challenges: &[Challenge],
+    state: &GameState,
+    constraints: &[Constraint],
+) -> Option<Sequence> {
+    // Gather the challenges left to attempt
+    let remaining: Vec<usize> = (0..challenges.len())
+        .filter(|idx| !state.is_completed(*idx))
+        .filter(|idx| !constraints.contains(&Constraint::Exclude { challenge: *idx }))
+        .collect();
+    // Place the pinned challenges
+    let mut slots = vec![None; remaining.len()];
+    for constraint in constraints {
+        if let Constraint::Pin { challenge, position } = *constraint {
+            if slots.contains(&Some(challenge)) {
+                return None;
+            }
+            match slots.get_mut(position) {
+                Some(slot @ None) if remaining.contains(&challenge) => *slot = Some(challenge),
+                _ => return None,
+            }
+        }
+    }
+    // Separate challenges with and without rewards. The order of challenges without rewards only
+    // matters if it is constrained.
+    let mut no_rewards = Vec::new();
+    let mut rewards = Vec::new();
+    for idx in remaining {
+        if slots.contains(&Some(idx)) {
+            continue;
+        }
+        match challenges[idx].reward {
+            Rewards::NONE if !constraints.iter().any(|c| c.orders(idx)) => no_rewards.push(idx),
+            _ => rewards.push(idx),
+        }
+    }
+    Some(Sequence {
+        slots,
+        rewards,
+        no_rewards,
+    })
+}
+
+/// Adds the vertices of a merged hull to the table, by the points spent on top of the owned dice.
+fn add_plans(
+    table: &mut [Alternatives],
+    merged_hull: Vec<ChallengesVertex>,
+    state: &GameState,
+    permutation: &[usize],
+) {
+    for candidate in merged_hull {
+        let totals = candidate.configuration.iter().fold(Abilities::new(), Abilities::add);
+        if !state.can_afford(&totals) {
+            continue;
+        }
+        let cost = state.to_buy(&totals).cost();
+        let idx = cost as usize / 2;
+        if !table.get(idx).is_some_and(|entry| entry.accepts(candidate.value)) {
+            continue;
+        }
+        table[idx].insert(SolutionVertex {
+            cost,
+            log_proba: candidate.log_proba,
+            value: candidate.value,
+            order: permutation.to_vec(),
+            abilities: candidate.configuration,
+        })
+    }
+}
+
+/// Returns whether the owned dice of a game in progress are split between the remaining
+/// challenges in every way, rather than in a few ways that may miss the best plan.
+pub fn splits_owned_dice_exactly(
+    challenges: &[Challenge],
+    state: &GameState,
+    constraints: &[Constraint],
+) -> bool {
+    state.dice == Abilities::new()
+        || sequence(challenges, state, constraints).is_none_or(|sequence| {
+            few_owned_splits(&state.dice, sequence.slots.len(), sequence.rewards.len())
+        })
+}
+
+/// Returns whether the hulls of every split of the owned dice between `challenges` challenges can
+/// be merged over every order of `ordered` challenges in reasonable time.
+fn few_owned_splits(dice: &Abilities, challenges: usize, ordered: usize) -> bool {
+    let mut merges: u128 = 1;
+    for i in 1..=ordered as u128 {
+        merges *= i;
+        if merges > MAX_OWNED_MERGES {
+            return false;
+        }
+    }
+    for ability in Ability::values() {
+        // The number of ways to split the dice of an ability is a binomial coefficient, whose
+        // partial products only grow.
+        for i in 1..=dice[ability] as u128 {
+            merges = merges * (challenges as u128 + i - 1) / i;
+            if merges > MAX_OWNED_MERGES {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Returns every way of splitting the owned dice between `challenges` challenges.
+fn owned_splits(dice: &Abilities, challenges: usize) -> Vec<Vec<Abilities>> {
+    let mut splits = vec![vec![Abilities::new(); challenges]];
+    for ability in Ability::values() {
+        let mut next = Vec::new();
+        for split in &splits {
+            for counts in compositions(dice[ability], challenges) {
+                let mut split = split.clone();
+                for (part, count) in split.iter_mut().zip(counts) {
+                    part[ability] = count;
                 }
-            },
-            None => {},
+                next.push(split);
+            }
+        }
+        splits = next;
+    }
+    splits
+}
+
+/// Returns every way of writing `total` as an ordered sum of `parts` counts.
+fn compositions(total: u8, parts: usize) -> Vec<Vec<u8>> {
+    if parts == 0 {
+        return if total == 0 { vec![vec![]] } else { vec![] };
+    }
+    let mut output = Vec::new();
+    for first in 0..=total {
+        for mut rest in compositions(total - first, parts - 1) {
+            rest.insert(0, first);
+            output.push(rest);
         }
     }
     output
 }
+
+/// Returns the most likely plan of a frontier that costs at most `budget`.
+pub fn best_within(frontier: &[SolutionVertex], budget: u32) -> Option<&SolutionVertex> {
+    frontier.iter().take_while(|vertex| vertex.cost <= budget).last()
+}
+
+/// Returns the budget beyond which every `step` extra points add less than `gain` to the success
+/// chance, or `None` if the frontier is empty.
+///
+/// Budgets are compared up to the cost of the last plan, so the knee is at most that cost.
+pub fn knee(frontier: &[SolutionVertex], step: u32, gain: f32) -> Option<u32> {
+    let first = frontier.first()?.cost;
+    let last = frontier.last()?.cost;
+    let proba = |budget| best_within(frontier, budget).map_or(0.0, |v| v.log_proba.exp());
+    // The gain of `step` extra points only changes at the costs of the plans, and `step` points
+    // before them.
+    let mut budgets: Vec<u32> = frontier
+        .iter()
+        .flat_map(|vertex| vec![vertex.cost, vertex.cost.saturating_sub(step)])
+        .filter(|budget| (first..=last).contains(budget))
+        .collect();
+    budgets.sort_unstable();
+    budgets.dedup();
+    // Find the last budget where the extra points still pay off.
+    let paying = budgets.iter().rposition(|budget| proba(budget + step) - proba(*budget) >= gain);
+    Some(match paying {
+        Some(idx) => budgets.get(idx + 1).copied().unwrap_or(last),
+        None => first,
+    })
+}
+
+/// Returns the cheapest plan of a frontier with a success chance of at least `proba`.
+pub fn cheapest_reaching(frontier: &[SolutionVertex], proba: f32) -> Option<&SolutionVertex> {
+    frontier.iter().find(|vertex| vertex.log_proba.exp() >= proba)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::OnceLock};
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::{challenge::search_table, *};
+
+    /// The largest budget compared against the reference solver.
+    const MAX_COST: u32 = 120;
+
+    const EPSILON: f32 = 1e-4;
+
+    /// The rewards offered by the random boards.
+    const REWARDS: [Rewards; 4] = [
+        Rewards::NONE,
+        Rewards::DICTION_STRENGTH,
+        Rewards::ATMOSPHERE_RANGE,
+        Rewards::ADDITIONAL_ABILITY,
+    ];
+
+    /// Returns the search tables of every combination of `REWARDS`.
+    fn tables() -> &'static SearchTables {
+        static TABLES: OnceLock<SearchTables> = OnceLock::new();
+        TABLES.get_or_init(|| {
+            let config = SolverConfig::default();
+            let mut tables = HashMap::new();
+            for rewards in Rewards::combinations() {
+                if REWARDS[1..3].iter().fold(rewards, |r, reward| r & !*reward).is_empty() {
+                    tables.insert(rewards, search_table(rewards, &config));
+                }
+            }
+            SearchTables { config, tables }
+        })
+    }
+
+    /// Returns a board of two to four challenges with low targets.
+    fn random_board(rng: &mut StdRng) -> Vec<Challenge> {
+        (0..rng.gen_range(2..=4))
+            .map(|idx| Challenge {
+                name: format!("challenge {}", idx),
+                target_idx: rng.gen_range(0..5),
+                abilities: rng.gen_range(2..=4),
+                reward: REWARDS[rng.gen_range(0..REWARDS.len())],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fixed_order_matches_reference() {
+        let config = SolverConfig::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut vertices = 0;
+        for _ in 0..20 {
+            let challenges = random_board(&mut rng);
+            for order in (0..challenges.len()).permutations(challenges.len()) {
+                let pins: Vec<Constraint> = order
+                    .iter()
+                    .enumerate()
+                    .map(|(position, challenge)| Constraint::Pin {
+                        challenge: *challenge,
+                        position,
+                    })
+                    .collect();
+                let state = GameState::new();
+                let frontier = solve_from(&challenges, tables(), &state, &pins, &config);
+                let reference =
+                    reference::solve_order(&challenges, &order, config.min_proba, MAX_COST);
+                // The merged hull is exact at its vertices.
+                for vertex in frontier.iter().filter(|v| v.cost <= MAX_COST) {
+                    let expected = reference[vertex.cost as usize / 2];
+                    assert!((vertex.log_proba - expected).abs() < EPSILON);
+                    vertices += 1;
+                }
+                // Between vertices, the merged hull can only be worse.
+                for budget in (0..=MAX_COST).step_by(2) {
+                    let log_proba = best_within(&frontier, budget)
+                        .map_or(f32::NEG_INFINITY, |v| v.log_proba);
+                    assert!(log_proba <= reference[budget as usize / 2] + EPSILON);
+                }
+            }
+        }
+        assert!(vertices > 0);
+    }
+
+    #[test]
+    fn test_pin_twice() {
+        let config = SolverConfig::default();
+        let mut rng = StdRng::seed_from_u64(3);
+        let challenges = random_board(&mut rng);
+        let pins = [
+            Constraint::Pin { challenge: 0, position: 0 },
+            Constraint::Pin { challenge: 0, position: 1 },
+        ];
+        assert!(solve_from(&challenges, tables(), &GameState::new(), &pins, &config).is_empty());
+    }
+
+    #[test]
+    fn test_total_cost_cap() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let challenges = random_board(&mut rng);
+        let config = SolverConfig::default();
+        let capped = SolverConfig {
+            max_total_cost: 60,
+            ..config
+        };
+        let frontier = solve(&challenges, tables(), &config);
+        let truncated = solve(&challenges, tables(), &capped);
+        assert!(!truncated.is_empty());
+        assert!(truncated.iter().all(|v| v.cost <= 60));
+        for (vertex, expected) in truncated.iter().zip(&frontier) {
+            assert_eq!(vertex.cost, expected.cost);
+        }
+    }
+
+    /// The solver is exact at the vertices of the merged hull of each order, but not globally. The
+    /// frontier may hold a vertex of one order at a cost where another order does better between
+    /// two of its own vertices. On these boards the success chance is off by up to about 0.07 at
+    /// some budgets, and the gap shrinks as the vertices get denser at higher budgets.
+    #[test]
+    fn test_solve_bounded_by_reference() {
+        let config = SolverConfig::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut gap: f32 = 0.0;
+        for _ in 0..20 {
+            let challenges = random_board(&mut rng);
+            let frontier = solve(&challenges, tables(), &config);
+            let reference = reference::solve(&challenges, config.min_proba, MAX_COST);
+            for vertex in frontier.iter().filter(|v| v.cost <= MAX_COST) {
+                let order =
+                    reference::solve_order(&challenges, &vertex.order, config.min_proba, MAX_COST);
+                assert!((vertex.log_proba - order[vertex.cost as usize / 2]).abs() < EPSILON);
+            }
+            for budget in (0..=MAX_COST).step_by(2) {
+                let log_proba =
+                    best_within(&frontier, budget).map_or(f32::NEG_INFINITY, |v| v.log_proba);
+                let expected = reference[budget as usize / 2];
+                assert!(log_proba <= expected + EPSILON);
+                gap = gap.max(expected.exp() - log_proba.exp());
+            }
+        }
+        assert!(gap < 0.07);
+    }
+
+    /// Plans that rely on the owned dice are found, and cost the points spent on top of them. The
+    /// owned dice are split between the two challenges in every way, so the only error left is the
+    /// one of merging the hulls, as in `test_solve_bounded_by_reference`. On these boards the
+    /// success chance is off by up to about 0.065.
+    #[test]
+    fn test_owned_dice_against_reference() {
+        let config = SolverConfig::default();
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut state = GameState::new();
+        state.dice[Ability::Rhythm] = 2;
+        state.dice[Ability::Diction] = 3;
+        let mut gap: f32 = 0.0;
+        for _ in 0..10 {
+            let mut challenges = random_board(&mut rng);
+            challenges.truncate(2);
+            let frontier = solve_from(&challenges, tables(), &state, &[], &config);
+            let reference =
+                reference::solve_owned(&challenges, &state.dice, config.min_proba, MAX_COST);
+            // The owned dice alone pass the challenges.
+            assert!(reference[0].is_finite());
+            assert_eq!(frontier[0].cost, 0);
+            for vertex in frontier.iter().filter(|v| v.cost <= MAX_COST) {
+                let totals = vertex.abilities.iter().fold(Abilities::new(), Abilities::add);
+                assert_eq!(state.to_buy(&totals).cost(), vertex.cost);
+            }
+            for budget in (0..=MAX_COST).step_by(2) {
+                let log_proba =
+                    best_within(&frontier, budget).map_or(f32::NEG_INFINITY, |v| v.log_proba);
+                let expected = reference[budget as usize / 2];
+                assert!(log_proba <= expected + EPSILON);
+                gap = gap.max(expected.exp() - log_proba.exp());
+            }
+        }
+        assert!(gap < 0.07);
+    }
+
+    #[test]
+    fn test_owned_splits() {
+        let mut dice = Abilities::new();
+        dice[Ability::Rhythm] = 2;
+        dice[Ability::Diction] = 3;
+        let splits = owned_splits(&dice, 2);
+        assert_eq!(splits.len(), 3 * 4);
+        assert!(splits.iter().all(|split| split[0] + &split[1] == dice));
+        assert!(few_owned_splits(&dice, 2, 2));
+        dice[Ability::Diction] = 10;
+        assert!(!few_owned_splits(&dice, 9, 5));
+    }
+
+    #[test]
+    fn test_cheapest_reaching() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let challenges = random_board(&mut rng);
+        let frontier = solve(&challenges, tables(), &SolverConfig::default());
+        let target = frontier.last().unwrap().log_proba.exp() * 0.9;
+        let vertex = cheapest_reaching(&frontier, target).unwrap();
+        assert!(vertex.log_proba.exp() >= target);
+        // Any cheaper budget falls short.
+        let cheaper = best_within(&frontier, vertex.cost - 1).map_or(0.0, |v| v.log_proba.exp());
+        assert!(cheaper < target);
+        assert!(cheapest_reaching(&frontier, 1.01).is_none());
+    }
+
+    #[test]
+    fn test_knee() {
+        let vertex = |cost, proba: f32| SolutionVertex {
+            cost,
+            log_proba: proba.ln(),
+            value: proba.ln(),
+            order: vec![],
+            abilities: vec![],
+        };
+        let frontier =
+            vec![vertex(100, 0.1), vertex(110, 0.5), vertex(120, 0.6), vertex(150, 0.61)];
+        // 10 more points add 0.4 up to 108, 0.1 up to 118, and 0.01 from 140 to 148.
+        assert_eq!(knee(&frontier, 10, 0.2), Some(110));
+        assert_eq!(knee(&frontier, 10, 0.05), Some(120));
+        assert_eq!(knee(&frontier, 10, 0.005), Some(150));
+        assert_eq!(knee(&frontier, 10, 1.0), Some(100));
+        assert_eq!(knee(&[], 10, 0.1), None);
+        // Costs need not be even.
+        assert_eq!(knee(&[vertex(100, 0.1), vertex(101, 0.5)], 1, 0.2), Some(101));
+    }
+
+    #[test]
+    fn test_expected_passed() {
+        let config = SolverConfig::default();
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let challenges = random_board(&mut rng);
+            let objective = Objective::Expected(vec![1.0; challenges.len()]);
+            let state = GameState::new();
+            let frontier =
+                solve_objective(&challenges, tables(), &state, &[], 1, &config, &objective);
+            // Every budget has a plan, even one that is unlikely to pass any challenge.
+            assert_eq!(frontier[0][0].cost, 0);
+            for (prev, next) in frontier.iter().zip(&frontier[1..]) {
+                assert!(prev[0].value < next[0].value);
+            }
+            for plans in &frontier {
+                assert!(plans[0].value <= challenges.len() as f32 + EPSILON);
+                assert!(plans[0].value >= challenges.len() as f32 * plans[0].log_proba.exp());
+            }
+        }
+    }
+}
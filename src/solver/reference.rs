@@ -0,0 +1,176 @@
+//! A slow reference solver for small boards.
+//!
+//! The reference solver tries every challenge order and every allocation of abilities to each
+//! challenge, and combines the challenges with an exact knapsack over the cost. It is only fast
+//! enough for boards of a few challenges with a small budget.
+
+use itertools::Itertools;
+
+use crate::{
+    abilities::{Abilities, Ability},
+    challenges::{Challenge, TARGET_SET},
+    distributions::Distribution,
+    rewards::Rewards,
+    state::GameState,
+};
+
+/// Returns the best log probability of passing every challenge at each even budget up to
+/// `max_cost`, over every challenge order.
+///
+/// Entry `i` holds the best plan costing at most `2 * i`, or negative infinity if there is none.
+/// Setups with a success probability of at most `min_proba` are ignored, like the solver does.
+pub fn solve(challenges: &[Challenge], min_proba: f32, max_cost: u32) -> Vec<f32> {
+    let mut output = vec![f32::NEG_INFINITY; max_cost as usize / 2 + 1];
+    for order in (0..challenges.len()).permutations(challenges.len()) {
+        let best = solve_order(challenges, &order, min_proba, max_cost);
+        for (output, best) in output.iter_mut().zip(best) {
+            *output = output.max(best);
+        }
+    }
+    output
+}
+
+/// Returns the best log probability of passing every challenge at each even budget up to
+/// `max_cost`, attempting the challenges in the given order.
+pub fn solve_order(
+    challenges: &[Challenge],
+    order: &[usize],
+    min_proba: f32,
+    max_cost: u32,
+) -> Vec<f32> {
+    let costs = max_cost as usize / 2 + 1;
+    // The best log probability at each exact cost.
+    let mut table = vec![f32::NEG_INFINITY; costs];
+    table[0] = 0.0;
+    let mut rewards = Rewards::NONE;
+    for idx in order {
+        let challenge = &challenges[*idx];
+        let curve = curve(challenge, rewards, min_proba, max_cost);
+        let mut next = vec![f32::NEG_INFINITY; costs];
+        for (cost, log_proba) in table.iter().enumerate() {
+            for (challenge_cost, challenge_log_proba) in curve.iter().enumerate() {
+                if let Some(entry) = next.get_mut(cost + challenge_cost) {
+                    *entry = entry.max(log_proba + challenge_log_proba);
+                }
+            }
+        }
+        table = next;
+        rewards |= challenge.reward;
+    }
+    // Allow plans that cost less than the budget.
+    for cost in 1..costs {
+        table[cost] = table[cost].max(table[cost - 1]);
+    }
+    table
+}
+
+/// Returns the best log probability of passing every challenge at each even number of points
+/// spent on top of the `owned` dice, up to `max_cost`, over every challenge order.
+///
+/// The owned dice are shared by every challenge. Every combination of setups is tried, so this is
+/// only fast enough for two or three challenges.
+pub fn solve_owned(
+    challenges: &[Challenge],
+    owned: &Abilities,
+    min_proba: f32,
+    max_cost: u32,
+) -> Vec<f32> {
+    let mut output = vec![f32::NEG_INFINITY; max_cost as usize / 2 + 1];
+    let state = GameState {
+        dice: *owned,
+        ..GameState::new()
+    };
+    for order in (0..challenges.len()).permutations(challenges.len()) {
+        let mut rewards = Rewards::NONE;
+        let mut options = Vec::new();
+        for idx in order {
+            let challenge = &challenges[idx];
+            options.push(setups(challenge, rewards, min_proba, max_cost + owned.cost()));
+            rewards |= challenge.reward;
+        }
+        search_owned(&options, Abilities::new(), 0.0, &state, &mut output);
+    }
+    // Allow plans that spend less than the budget.
+    for cost in 1..output.len() {
+        output[cost] = output[cost].max(output[cost - 1]);
+    }
+    output
+}
+
+/// Tries every combination of the setups of the remaining challenges, recording the best log
+/// probability at each exact number of points spent.
+fn search_owned(
+    options: &[Vec<(Abilities, f32)>],
+    totals: Abilities,
+    log_proba: f32,
+    state: &GameState,
+    output: &mut [f32],
+) {
+    let spent = state.to_buy(&totals).cost() as usize / 2;
+    if spent >= output.len() {
+        return;
+    }
+    let (setups, rest) = match options.split_first() {
+        Some(split) => split,
+        None => {
+            output[spent] = output[spent].max(log_proba);
+            return;
+        }
+    };
+    for (abilities, setup_log_proba) in setups {
+        search_owned(rest, totals + abilities, log_proba + setup_log_proba, state, output);
+    }
+}
+
+/// Returns the best log probability of passing a challenge at each exact even cost.
+fn curve(challenge: &Challenge, rewards: Rewards, min_proba: f32, max_cost: u32) -> Vec<f32> {
+    let mut output = vec![f32::NEG_INFINITY; max_cost as usize / 2 + 1];
+    for (abilities, log_proba) in setups(challenge, rewards, min_proba, max_cost) {
+        let entry = &mut output[abilities.cost() as usize / 2];
+        *entry = entry.max(log_proba);
+    }
+    output
+}
+
+/// Returns every setup of a challenge costing at most `max_cost`, with the log probability of
+/// passing it, ignoring setups with a success probability of at most `min_proba`.
+fn setups(
+    challenge: &Challenge,
+    rewards: Rewards,
+    min_proba: f32,
+    max_cost: u32,
+) -> Vec<(Abilities, f32)> {
+    let max_abilities =
+        challenge.abilities as usize + rewards.contains(Rewards::ADDITIONAL_ABILITY) as usize;
+    let target = TARGET_SET[challenge.target_idx];
+    allocations(0, max_abilities, max_cost, Abilities::new())
+        .into_iter()
+        .map(|abilities| (abilities, Distribution::of(&abilities, rewards).at_least(target)))
+        .filter(|(_, proba)| *proba > min_proba)
+        .map(|(abilities, proba)| (abilities, proba.ln()))
+        .collect()
+}
+
+/// Returns every allocation of at most `max_abilities` abilities costing at most `max_cost`,
+/// extending `abilities` with the abilities from `ability_idx` onwards.
+fn allocations(
+    ability_idx: usize,
+    max_abilities: usize,
+    max_cost: u32,
+    abilities: Abilities,
+) -> Vec<Abilities> {
+    let ability = match Ability::values().get(ability_idx) {
+        Some(ability) => *ability,
+        None => return vec![abilities],
+    };
+    let mut output = Vec::new();
+    let mut abilities = abilities;
+    loop {
+        output.extend(allocations(ability_idx + 1, max_abilities, max_cost, abilities));
+        abilities[ability] += 1;
+        let count: usize = Ability::values().iter().map(|a| abilities[*a] as usize).sum();
+        if count > max_abilities || abilities.cost() > max_cost {
+            return output;
+        }
+    }
+}
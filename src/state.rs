@@ -0,0 +1,119 @@
+use crate::{
+    abilities::{Abilities, Ability},
+    challenges::Challenge,
+    rewards::Rewards,
+};
+
+/// The state of a game in progress.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct GameState {
+    /// The indices of the challenges that have already been passed.
+    pub completed: Vec<usize>,
+
+    /// The abilities that are still owned.
+    pub dice: Abilities,
+
+    /// The points left to buy additional abilities with, or `None` if the budget is unlimited.
+    pub points: Option<u32>,
+}
+
+impl GameState {
+    /// Returns the state at the start of a game with an unlimited budget.
+    pub fn new() -> GameState {
+        Default::default()
+    }
+
+    /// Returns whether the challenge has already been passed.
+    pub fn is_completed(&self, challenge_idx: usize) -> bool {
+        self.completed.contains(&challenge_idx)
+    }
+
+    /// Returns the rewards collected from the completed challenges.
+    pub fn rewards(&self, challenges: &[Challenge]) -> Rewards {
+        self.completed
+            .iter()
+            .fold(Rewards::NONE, |rewards, idx| rewards | challenges[*idx].reward)
+    }
+
+    /// Returns the abilities that must be bought in addition to the owned dice.
+    pub fn to_buy(&self, totals: &Abilities) -> Abilities {
+        let mut output = Abilities::new();
+        for ability in Ability::values() {
+            output[ability] = totals[ability].saturating_sub(self.dice[ability]);
+        }
+        output
+    }
+
+    /// Returns whether the abilities can be assembled from the owned dice and the points left.
+    pub fn can_afford(&self, totals: &Abilities) -> bool {
+        self.points.is_none_or(|points| self.to_buy(totals).cost() <= points)
+    }
+
+    /// Returns the points left after buying the abilities, or `None` if the budget is unlimited or
+    /// the abilities cannot be afforded.
+    pub fn points_left(&self, totals: &Abilities) -> Option<u32> {
+        self.points.and_then(|points| points.checked_sub(self.to_buy(totals).cost()))
+    }
+
+    /// Rolls the abilities on an attempt, buying the ones that are not owned with the points left.
+    ///
+    /// Returns `false` without changing the state if the abilities cannot be afforded.
+    pub fn spend(&mut self, abilities: &Abilities) -> bool {
+        if !self.can_afford(abilities) {
+            return false;
+        }
+        let to_buy = self.to_buy(abilities);
+        if let Some(points) = &mut self.points {
+            *points -= to_buy.cost();
+        }
+        for ability in Ability::values() {
+            self.dice[ability] = self.dice[ability] + to_buy[ability] - abilities[ability];
+        }
+        true
+    }
+
+    /// Returns a die to the owned dice, up to the most dice of an ability that can be tracked.
+    pub fn refund(&mut self, ability: Ability) {
+        self.dice[ability] = self.dice[ability].saturating_add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spend() {
+        let mut state = GameState {
+            completed: vec![],
+            dice: Abilities::new(),
+            points: Some(10),
+        };
+        state.dice[Ability::Diction] = 3;
+
+        let mut abilities = Abilities::new();
+        abilities[Ability::Diction] = 2;
+        abilities[Ability::Atmosphere] = 1;
+        assert_eq!(state.points_left(&abilities), Some(10 - Ability::Atmosphere.cost()));
+        assert!(state.spend(&abilities));
+        assert_eq!(state.dice[Ability::Diction], 1);
+        assert_eq!(state.dice[Ability::Atmosphere], 0);
+        assert_eq!(state.points, Some(10 - Ability::Atmosphere.cost()));
+
+        abilities[Ability::Atmosphere] = 5;
+        assert_eq!(state.points_left(&abilities), None);
+        assert!(!state.spend(&abilities));
+        assert_eq!(state.dice[Ability::Diction], 1);
+
+        state.refund(Ability::Diction);
+        assert_eq!(state.dice[Ability::Diction], 2);
+    }
+
+    #[test]
+    fn test_refund_saturates() {
+        let mut state = GameState::new();
+        state.dice[Ability::Style] = u8::MAX;
+        state.refund(Ability::Style);
+        assert_eq!(state.dice[Ability::Style], u8::MAX);
+    }
+}